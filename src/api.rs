@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use rab_core::{armor_and_skills::Skill, build_search::Build};
+use ron::Error;
+
+use crate::{
+    blacklist::{get_blacklist, BLACKLIST_FILE},
+    build_stats::{sort_builds, BuildSort},
+    file::{get_all_armors_from_file, get_talismans, TALISMANS_FILE},
+    paths::Paths,
+    search::{ArmorLists, SearchJob},
+    search_form::SearchForm,
+};
+
+// The entry point of the tools using rab as a library: the armors of a
+// data directory and the searches on them.
+pub struct Engine {
+    armors: ArmorLists,
+    sort: BuildSort,
+    jewel_inventory: Option<HashMap<Skill, u8>>, // None = unlimited jewels
+}
+
+impl Engine {
+    pub fn new(armors: ArmorLists) -> Self {
+        Self {
+            armors,
+            sort: BuildSort::default(),
+            jewel_inventory: None,
+        }
+    }
+
+    // Only the armor files are required, without talismans
+    // or blacklist the lists are empty.
+    pub fn load(paths: &Paths) -> Result<Self, Error> {
+        let armors = get_all_armors_from_file(paths)?;
+        let talismans = get_talismans(&paths.data_file(TALISMANS_FILE)).unwrap_or_default();
+        let blacklist = get_blacklist(&paths.data_file(BLACKLIST_FILE)).unwrap_or_default();
+        Ok(Self::new(ArmorLists::new(armors, talismans, &blacklist)))
+    }
+
+    pub fn armors(&self) -> &ArmorLists {
+        &self.armors
+    }
+
+    pub fn set_sort(&mut self, sort: BuildSort) {
+        self.sort = sort;
+    }
+
+    pub fn set_jewel_inventory(&mut self, jewel_inventory: Option<HashMap<Skill, u8>>) {
+        self.jewel_inventory = jewel_inventory;
+    }
+
+    // To follow the progress or cancel the search: the steps of
+    // the job can be run from another thread.
+    pub fn job(&self, form: &SearchForm) -> SearchJob {
+        SearchJob::new(
+            form.query(Default::default(), self.jewel_inventory.clone()),
+            self.armors.clone(),
+        )
+    }
+
+    // Runs every step of the search in the current thread,
+    // the builds are sorted like in the interface.
+    pub fn search(&self, form: &SearchForm) -> Vec<Build> {
+        let job = self.job(form);
+        let mut builds = job.run_all();
+        let query = job.query();
+        sort_builds(
+            &mut builds,
+            self.sort,
+            &query.wishes,
+            &query.optional_wishes,
+            &query.weapon_slots,
+        );
+        builds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        search::tests::{armor, lists},
+        search_form::{FormWish, WishKind},
+    };
+
+    fn form(level: u8) -> SearchForm {
+        SearchForm {
+            wishes: vec![FormWish {
+                skill: Skill::AttackBoost,
+                level,
+                kind: WishKind::Required,
+                weight: 1,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn helmet_names(builds: &[Build]) -> Vec<String> {
+        builds
+            .iter()
+            .map(|build| build.helmet.as_ref().unwrap().0.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn search_sorts_and_uses_the_jewel_inventory() {
+        let mut low = armor("low", &[(Skill::AttackBoost, 1)], &[2]);
+        low.defense = 10;
+        low.fire = 3;
+        let mut high = armor("high", &[(Skill::AttackBoost, 2)], &[1]);
+        high.defense = 50;
+        high.fire = -2;
+        let mut engine = Engine::new(lists(vec![low, high], vec![], vec![]));
+
+        assert_eq!(helmet_names(&engine.search(&form(1))), ["high", "low"]);
+        engine.set_sort(BuildSort::Fire);
+        assert_eq!(helmet_names(&engine.search(&form(1))), ["low", "high"]);
+
+        // the second level needs a jewel with the low defense helmet
+        assert_eq!(helmet_names(&engine.search(&form(2))), ["low", "high"]);
+        engine.set_jewel_inventory(Some(HashMap::new()));
+        assert_eq!(helmet_names(&engine.search(&form(2))), ["high"]);
+    }
+}
@@ -0,0 +1,651 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::Mutex,
+};
+
+use lexical_sort::natural_lexical_cmp;
+use rab_core::{
+    armor_and_skills::{Armor, Skill},
+    build_search::Build,
+};
+
+use crate::{
+    blacklist::{get_blacklist, save_blacklist, Blacklist, BLACKLIST_FILE},
+    build_stats::{build_key, sort_builds, ArmorSlot, BuildKey, BuildSort},
+    export::{export_builds, save_export, ExportFormat},
+    file::{
+        get_all_armors_from_file, get_jewel_inventory, get_saved_builds, get_talismans,
+        save_builds, save_jewel_inventory, save_talismans_to_file, BUILDS_FILE, JEWELS_FILE,
+        TALISMANS_FILE,
+    },
+    locale::{get_locales, Locale, LocalizedSkill},
+    paths::Paths,
+    profile::{get_profile, save_profile, Profile},
+    search::{ArmorLists, SearchJob, SearchQuery},
+    search_form::{get_presets, save_presets, SearchForm},
+    session::{get_session, save_session, Session, SessionResults},
+    share::{build_from_share_code, ShareCodeError},
+    LOCALE,
+};
+
+// in the data directory
+const SESSION_FILE: &str = "session.ron";
+const PRESETS_FILE: &str = "presets.ron";
+
+// Everything RAB loads and saves, without any widget. The frontends
+// keep their own form fields and give a SearchForm to the state.
+#[derive(Default)]
+pub struct AppState {
+    pub paths: Paths,
+
+    pub helmets: Vec<Armor>,
+    pub chests: Vec<Armor>,
+    pub arms: Vec<Armor>,
+    pub waists: Vec<Armor>,
+    pub legs: Vec<Armor>,
+    pub talismans: Vec<Armor>,
+    pub armors_missing: bool, // the armor files must be downloaded
+
+    pub locales: HashMap<String, Locale>,
+    pub selected_locale: String,
+
+    pub profile: Profile,
+
+    pub saved_builds: HashMap<String, Build>,
+    pub blacklist: Blacklist,
+    pub jewel_inventory: HashMap<Skill, u8>,
+    pub presets: HashMap<String, SearchForm>,
+
+    pub locked_armors: [Option<Armor>; 6], // same order as ArmorSlot::ALL
+
+    pub results: SearchResults,
+
+    pub messages: Messages,
+}
+
+// The messages about the files. They are printed on the standard output,
+// or kept for a frontend drawing the whole terminal like the TUI.
+#[derive(Default)]
+pub struct Messages(Option<Mutex<Vec<String>>>);
+
+impl Messages {
+    pub fn kept() -> Self {
+        Self(Some(Mutex::default()))
+    }
+
+    pub fn report(&self, message: String) {
+        match &self.0 {
+            Some(kept) => kept.lock().unwrap().push(message),
+            None => println!("{}", message),
+        }
+    }
+
+    // the kept messages since the last call, oldest first
+    pub fn take(&self) -> Vec<String> {
+        match &self.0 {
+            Some(kept) => mem::take(&mut *kept.lock().unwrap()),
+            None => Vec::new(),
+        }
+    }
+}
+
+// The builds of the last search, with the wishes used to sort them
+#[derive(Default)]
+pub struct SearchResults {
+    pub builds: Vec<Build>,
+    pub wishes: Vec<(Skill, u8)>,
+    pub optional_wishes: Vec<(Skill, u8, u8)>,
+    pub weapon_slots: [u8; 3], // their free slots are counted with the armor's
+    found: HashSet<BuildKey>,  // the variants of the wishes find the same builds
+}
+
+impl SearchResults {
+    pub fn new(query: &SearchQuery) -> Self {
+        Self {
+            wishes: query.wishes.clone(),
+            optional_wishes: query.optional_wishes.clone(),
+            weapon_slots: query.weapon_slots,
+            ..Default::default()
+        }
+    }
+
+    // the builds already found are ignored
+    pub fn add(&mut self, builds: Vec<Build>, sort: BuildSort) {
+        let found = &mut self.found;
+        self.builds.extend(
+            builds
+                .into_iter()
+                .filter(|build| found.insert(build_key(build))),
+        );
+        self.sort(sort);
+    }
+
+    pub fn sort(&mut self, sort: BuildSort) {
+        sort_builds(
+            &mut self.builds,
+            sort,
+            &self.wishes,
+            &self.optional_wishes,
+            &self.weapon_slots,
+        );
+    }
+}
+
+// What the talisman editor changes
+#[derive(Default, Clone)]
+pub struct TalismanDraft {
+    pub name: String,
+    pub skills: Vec<(Skill, u8)>,
+    pub slots: [u8; 3],
+}
+
+impl TalismanDraft {
+    pub fn from_talisman(talisman: &Armor) -> Self {
+        let mut slots = [0; 3];
+        for (slot, size) in slots.iter_mut().zip(&talisman.slots) {
+            *slot = *size;
+        }
+        Self {
+            name: talisman.name.clone(),
+            skills: talisman.skills.clone(),
+            slots,
+        }
+    }
+
+    pub fn apply(&self, talisman: &mut Armor) {
+        talisman.name = self.name.clone();
+        talisman.skills = self.skills.clone();
+        talisman.slots = self
+            .slots
+            .iter()
+            .filter(|slot| **slot > 0)
+            .copied()
+            .collect();
+    }
+}
+
+impl AppState {
+    // Reads every file. The search form of the last session is
+    // returned for the frontend.
+    pub fn load(
+        paths: Paths,
+        lang: Option<String>,
+        messages: Messages,
+    ) -> (Self, Option<SearchForm>) {
+        let talismans = load_talismans(&paths, &messages);
+
+        let (armors, armors_missing) = match get_all_armors_from_file(&paths) {
+            Ok(lists) => (lists, false),
+            Err(_) => ((vec![], vec![], vec![], vec![], vec![]), true),
+        };
+        let (helmets, chests, arms, waists, legs) = armors;
+
+        let locales = load_locales(&paths, &messages);
+
+        let profile = load_or_default(
+            &messages,
+            ("Profile", "Empty profile"),
+            get_profile(&paths.profile_file()),
+        );
+
+        // the command line doesn't change the profile
+        let selected_locale = lang.unwrap_or_else(|| profile.lang.clone());
+        if !locales.is_empty() && !locales.contains_key(&selected_locale) {
+            messages.report(format!("Unknown language: {}", selected_locale));
+        }
+        *LOCALE.lock().unwrap() = locales.get(&selected_locale).cloned();
+
+        let saved_builds = load_or_default(
+            &messages,
+            ("Builds", "No builds"),
+            get_saved_builds(&paths.data_file(BUILDS_FILE)),
+        );
+        let session = load_or_default(
+            &messages,
+            ("Session", "Empty session"),
+            get_session(&paths.data_file(SESSION_FILE)),
+        );
+        let presets = load_or_default(
+            &messages,
+            ("Presets", "No preset"),
+            get_presets(&paths.data_file(PRESETS_FILE)),
+        );
+        let blacklist = load_or_default(
+            &messages,
+            ("Blacklist", "Empty blacklist"),
+            get_blacklist(&paths.data_file(BLACKLIST_FILE)),
+        );
+
+        let mut state = Self {
+            helmets,
+            chests,
+            arms,
+            waists,
+            legs,
+            talismans,
+            armors_missing,
+            locales,
+            selected_locale,
+            profile,
+            saved_builds,
+            blacklist,
+            jewel_inventory: load_jewel_inventory(&paths, &messages),
+            presets,
+            paths,
+            messages,
+            ..Default::default()
+        };
+
+        let form = state.restore_session(session);
+
+        (state, form)
+    }
+
+    fn restore_session(&mut self, session: Session) -> Option<SearchForm> {
+        if let (true, Some(results)) = (self.profile.restore_results, session.results) {
+            self.results = SearchResults {
+                builds: results.builds,
+                wishes: results.wishes,
+                optional_wishes: results.optional_wishes,
+                weapon_slots: results.weapon_slots,
+                ..Default::default()
+            };
+        }
+        for (slot, name) in ArmorSlot::ALL.iter().zip(session.locked_armors) {
+            if let Some(name) = name {
+                // the piece may have been removed from the armor files
+                self.locked_armors[slot.index()] = self
+                    .armor_list(*slot)
+                    .iter()
+                    .find(|armor| armor.name == name)
+                    .cloned();
+            }
+        }
+        session.form
+    }
+
+    // Saved by each search and on exit, so only the errors are shown.
+    pub fn save_session(&self, form: SearchForm) {
+        let session = Session {
+            locked_armors: self
+                .locked_armors
+                .clone()
+                .map(|armor| armor.map(|armor| armor.name)),
+            form: Some(form),
+            results: if self.profile.restore_results {
+                Some(SessionResults {
+                    wishes: self.results.wishes.clone(),
+                    optional_wishes: self.results.optional_wishes.clone(),
+                    weapon_slots: self.results.weapon_slots,
+                    builds: self.results.builds.clone(),
+                })
+            } else {
+                None
+            },
+        };
+        if let Err(err) = save_session(&session, &self.paths.data_file(SESSION_FILE)) {
+            self.messages
+                .report(format!("Unable to save the session:\n{}", err))
+        }
+    }
+
+    pub fn save_profile(&self) {
+        match save_profile(&self.profile, &self.paths.profile_file()) {
+            Ok(file) => self.messages.report(format!("Profile saved to {}", file)),
+            Err(err) => self
+                .messages
+                .report(format!("Can't save profile:\n{}", err)),
+        };
+    }
+
+    pub fn reload_locales(&mut self) {
+        self.locales = load_locales(&self.paths, &self.messages);
+        *LOCALE.lock().unwrap() = self.locales.get(&self.selected_locale).cloned();
+    }
+
+    pub fn change_locale(&mut self, locale: String) {
+        self.profile.lang = locale.clone();
+        *LOCALE.lock().unwrap() = self.locales.get(&locale).cloned();
+        // after the unwrap(), if there is a mystical problem with the mutex
+        // it is better to not save the locale
+        self.save_profile();
+        self.selected_locale = locale;
+    }
+
+    pub fn reload_armors(&mut self) {
+        let (helmets, chests, arms, waists, legs) = match get_all_armors_from_file(&self.paths) {
+            Ok(lists) => lists,
+            Err(err) => {
+                self.messages
+                    .report(format!("ERROR: Can't reload armors:\n{}", err));
+                (vec![], vec![], vec![], vec![], vec![])
+            }
+        };
+        self.helmets = helmets;
+        self.chests = chests;
+        self.arms = arms;
+        self.waists = waists;
+        self.legs = legs;
+    }
+
+    pub fn armor_list(&self, slot: ArmorSlot) -> &[Armor] {
+        match slot {
+            ArmorSlot::Helmet => &self.helmets,
+            ArmorSlot::Chest => &self.chests,
+            ArmorSlot::Arm => &self.arms,
+            ArmorSlot::Waist => &self.waists,
+            ArmorSlot::Leg => &self.legs,
+            ArmorSlot::Talisman => &self.talismans,
+        }
+    }
+
+    // every armor without the talismans
+    pub fn all_armors(&self) -> impl Iterator<Item = &Armor> {
+        self.helmets
+            .iter()
+            .chain(self.chests.iter())
+            .chain(self.arms.iter())
+            .chain(self.waists.iter())
+            .chain(self.legs.iter())
+    }
+
+    pub fn armor_lists(&self) -> ArmorLists {
+        ArmorLists::new(
+            (
+                self.helmets.clone(),
+                self.chests.clone(),
+                self.arms.clone(),
+                self.waists.clone(),
+                self.legs.clone(),
+            ),
+            self.talismans.clone(),
+            &self.blacklist,
+        )
+    }
+
+    // the skills sorted in the current language
+    pub fn sorted_skills(&self) -> Vec<LocalizedSkill> {
+        let mut skills: Vec<LocalizedSkill> =
+            Skill::ALL.iter().map(|s| LocalizedSkill(*s)).collect();
+        skills.sort_unstable_by(|a, b| natural_lexical_cmp(&a.to_string(), &b.to_string()));
+        skills
+    }
+
+    pub fn search_query(&self, form: &SearchForm) -> SearchQuery {
+        form.query(
+            self.locked_armors.clone(),
+            if self.profile.ignore_jewel_inventory {
+                None
+            } else {
+                Some(self.jewel_inventory.clone())
+            },
+        )
+    }
+
+    pub fn search_job(&self, form: &SearchForm) -> SearchJob {
+        SearchJob::new(self.search_query(form), self.armor_lists())
+    }
+
+    // The previous results are cleared, the builds of the job
+    // must then be given to add_builds.
+    pub fn start_search(&mut self, form: &SearchForm) -> SearchJob {
+        let job = self.search_job(form);
+        self.results = SearchResults::new(job.query());
+        job
+    }
+
+    pub fn add_builds(&mut self, builds: Vec<Build>) {
+        self.results.add(builds, self.profile.sort);
+    }
+
+    pub fn change_sort(&mut self, sort: BuildSort) {
+        self.results.sort(sort);
+        self.profile.sort = sort;
+        self.save_profile()
+    }
+
+    pub fn set_ignore_jewel_inventory(&mut self, ignore: bool) {
+        self.profile.ignore_jewel_inventory = ignore;
+        self.save_profile()
+    }
+
+    pub fn set_restore_results(&mut self, restore: bool) {
+        self.profile.restore_results = restore;
+        self.save_profile()
+    }
+
+    pub fn save_builds(&self) {
+        match save_builds(&self.saved_builds, &self.paths.data_file(BUILDS_FILE)) {
+            Ok(path) => self.messages.report(format!("Builds saved to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to save builds:\n{}", err)),
+        }
+    }
+
+    pub fn save_build(&mut self, name: String, build: Build) {
+        self.saved_builds.insert(name, build);
+        self.save_builds()
+    }
+
+    // next to the builds file, builds.md or builds.txt
+    pub fn export_saved_builds(&self, format: ExportFormat) {
+        let path = self
+            .paths
+            .data_file(&format!("builds.{}", format.extension()));
+        match save_export(&export_builds(&self.saved_builds, format), &path) {
+            Ok(path) => self.messages.report(format!("Builds exported to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to export builds:\n{}", err)),
+        }
+    }
+
+    // the pieces are searched in the loaded armors, not in the saved builds
+    pub fn build_from_share_code(&self, code: &str) -> Result<Build, ShareCodeError> {
+        build_from_share_code(
+            code,
+            [
+                &self.helmets,
+                &self.chests,
+                &self.arms,
+                &self.waists,
+                &self.legs,
+            ],
+        )
+    }
+
+    pub fn rename_saved_build(&mut self, name: &str, new_name: String) {
+        if let Some(build) = self.saved_builds.remove(name) {
+            self.saved_builds.insert(new_name, build);
+            self.save_builds()
+        }
+    }
+
+    pub fn remove_saved_build(&mut self, name: &str) {
+        self.saved_builds.remove(name);
+        self.save_builds()
+    }
+
+    pub fn save_blacklist(&self) {
+        match save_blacklist(&self.blacklist, &self.paths.data_file(BLACKLIST_FILE)) {
+            Ok(path) => self.messages.report(format!("Blacklist saved to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to save the blacklist:\n{}", err)),
+        }
+    }
+
+    pub fn save_presets(&self) {
+        match save_presets(&self.presets, &self.paths.data_file(PRESETS_FILE)) {
+            Ok(path) => self.messages.report(format!("Presets saved to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to save the presets:\n{}", err)),
+        }
+    }
+
+    pub fn save_jewel_inventory(&self) {
+        match save_jewel_inventory(&self.jewel_inventory, &self.paths.data_file(JEWELS_FILE)) {
+            Ok(path) => self
+                .messages
+                .report(format!("Jewel inventory saved to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to save the jewel inventory:\n{}", err)),
+        }
+    }
+
+    pub fn reload_jewel_inventory(&mut self) {
+        self.jewel_inventory = load_jewel_inventory(&self.paths, &self.messages);
+    }
+
+    pub fn add_talisman(&mut self) {
+        self.talismans.push(Armor {
+            name: "New talisman".to_string(),
+            skills: vec![(Skill::Botanist, 1)],
+            ..Default::default()
+        });
+    }
+
+    pub fn save_talismans(&self) {
+        match save_talismans_to_file(&self.talismans, &self.paths.data_file(TALISMANS_FILE)) {
+            Ok(path) => self.messages.report(format!("Talismans saved to {}", path)),
+            Err(err) => self
+                .messages
+                .report(format!("Unable to save the talismans: {}", err)),
+        }
+    }
+
+    pub fn reload_talismans(&mut self) {
+        self.talismans = load_talismans(&self.paths, &self.messages);
+    }
+}
+
+// the skills of the build, the highest levels last
+pub fn skill_totals(build: &Build) -> Vec<(Skill, u8)> {
+    let mut skills: Vec<(Skill, u8)> = build.get_all_skills_and_amounts().drain().collect();
+    skills.sort_unstable_by_key(|(_, amount)| *amount);
+    skills
+}
+
+// A file that can't be read gives an empty value, RAB still starts.
+// The name of the file comes first, then the name of the empty value.
+fn load_or_default<T: Default>(
+    messages: &Messages,
+    (file, empty): (&str, &str),
+    result: Result<T, ron::Error>,
+) -> T {
+    match result {
+        Ok(value) => {
+            messages.report(format!("{} file succesfully loaded.", file));
+            value
+        }
+        Err(err) => {
+            messages.report(format!(
+                "Can't read the {} file:\n{}\n{} loaded.",
+                file.to_lowercase(),
+                err,
+                empty
+            ));
+            T::default()
+        }
+    }
+}
+
+fn load_talismans(paths: &Paths, messages: &Messages) -> Vec<Armor> {
+    load_or_default(
+        messages,
+        ("Talisman", "Empty talisman list"),
+        get_talismans(&paths.data_file(TALISMANS_FILE)),
+    )
+}
+
+fn load_locales(paths: &Paths, messages: &Messages) -> HashMap<String, Locale> {
+    match get_locales(&paths.locale_dir) {
+        Ok(locales) => locales,
+        Err(err) => {
+            messages.report(format!(
+                "Error with localization files at {}\n{}\nWARNING: no locale loaded.",
+                paths.locale_dir.display(),
+                err
+            ));
+            HashMap::with_capacity(0)
+        }
+    }
+}
+
+fn load_jewel_inventory(paths: &Paths, messages: &Messages) -> HashMap<Skill, u8> {
+    load_or_default(
+        messages,
+        ("Jewel", "Empty jewel inventory"),
+        get_jewel_inventory(&paths.data_file(JEWELS_FILE)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::{armor, lists, query};
+
+    #[test]
+    fn found_builds_are_counted_once() {
+        let armors = lists(
+            vec![
+                armor("h1", &[(Skill::AttackBoost, 1)], &[]),
+                armor("h2", &[(Skill::Botanist, 1)], &[2]),
+            ],
+            vec![armor("c", &[(Skill::AttackBoost, 1)], &[])],
+            vec![],
+        );
+        let query = query(&[(Skill::AttackBoost, 2)]);
+        let job = SearchJob::new(query.clone(), armors);
+
+        let mut results = SearchResults::new(&query);
+        for step in 0..job.step_count() {
+            results.add(job.run_step(step).unwrap(), BuildSort::Defense);
+        }
+        let count = results.builds.len();
+        assert!(count > 0);
+        assert_eq!(count, job.run_all().len());
+
+        // a step found again doesn't change the count
+        results.add(job.run_all(), BuildSort::Defense);
+        assert_eq!(results.builds.len(), count);
+    }
+
+    #[test]
+    fn talisman_draft_keeps_the_used_slots() {
+        let mut talisman = armor("Talisman", &[(Skill::Botanist, 2)], &[2, 1]);
+        let mut draft = TalismanDraft::from_talisman(&talisman);
+        assert_eq!(draft.slots, [2, 1, 0]);
+
+        draft.name = "Renamed".to_string();
+        draft.slots = [3, 0, 1];
+        draft.apply(&mut talisman);
+        assert_eq!(talisman.name, "Renamed");
+        assert_eq!(talisman.skills, [(Skill::Botanist, 2)]);
+        assert_eq!(talisman.slots, [3, 1]);
+    }
+
+    #[test]
+    fn skill_totals_are_sorted_by_amount() {
+        let build = Build {
+            helmet: Some((
+                armor("h", &[(Skill::AttackBoost, 2), (Skill::Botanist, 1)], &[2]),
+                [Some(Skill::AttackBoost), None, None],
+            )),
+            chest: None,
+            arm: None,
+            waist: None,
+            leg: None,
+            talisman: None,
+            weapon_jewels: [None; 3],
+        };
+        assert_eq!(
+            skill_totals(&build),
+            [(Skill::Botanist, 1), (Skill::AttackBoost, 3)]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    env,
+    fs::File,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use rab::{
+    build_stats::{optional_wishes_met, BuildSort},
+    cli::{self, Args},
+    export::{export_build, ExportFormat},
+    locale::{get_locales, InterfaceSymbol, LocalizedSkill},
+    profile::get_profile,
+    search_form::{FormWish, SearchForm, WishKind},
+    server, Engine, LOCALE,
+};
+use rab_core::{
+    armor_and_skills::{Gender, Skill},
+    build_search::Build,
+};
+
+const USAGE: &str = "Usage: rab-cli [OPTIONS] [--query <FILE>] [--wish <SKILL=LEVEL>]...
+       rab-cli serve [OPTIONS]   Local HTTP API, see rab-cli serve --help
+
+Search options (added to the query file if any):
+  --query <FILE>          Search form in RON, or JSON with the .json extension
+  --wish <SKILL=LEVEL>    Required skill, by name or localized name
+  --optional <SKILL=LEVEL>
+                          Optional skill
+  --avoid <SKILL=LEVEL>   Skill with its highest allowed level
+  --gender <GENDER>       female or male
+  --weapon-slots <A,B,C>  Sizes of the weapon slots, like 3,1,0
+
+Output options:
+  --sort <KEY>            defense, fire, water, thunder, ice, dragon,
+                          free_slots or extra_skills
+  --limit <N>             Number of builds printed
+  --json                  Print the builds in JSON
+
+Files options:
+  --data-dir <DIR>        Directory of every RAB file (profile included)
+  --armors-dir <DIR>      Directory of the armor files
+  --locale-dir <DIR>      Directory of the locale files
+  --lang <NAME>           Language of the skills and the armors, like English
+  -h, --help              Print this help";
+
+// The options only known by rab-cli
+#[derive(Default)]
+struct SearchArgs {
+    query: Option<PathBuf>,
+    wishes: Vec<(String, u8, WishKind)>,
+    gender: Option<Gender>,
+    weapon_slots: Option<[u8; 3]>,
+    sort: Option<BuildSort>,
+    limit: Option<usize>,
+    json: bool,
+}
+
+fn main() {
+    if env::args().nth(1).as_deref() == Some("serve") {
+        return serve();
+    }
+
+    let mut search_args = SearchArgs::default();
+    let args = cli::parse_args_with(env::args().skip(1), |name, value| {
+        match name {
+            "--json" => search_args.json = true,
+            "--query" => search_args.query = Some(value()?.into()),
+            "--wish" => search_args
+                .wishes
+                .push(parse_wish(&value()?, WishKind::Required)?),
+            "--optional" => search_args
+                .wishes
+                .push(parse_wish(&value()?, WishKind::Optional)?),
+            "--avoid" => search_args
+                .wishes
+                .push(parse_wish(&value()?, WishKind::Avoid)?),
+            "--gender" => {
+                let gender = value()?;
+                search_args.gender = Some(match gender.as_str() {
+                    "female" => Gender::Female,
+                    "male" => Gender::Male,
+                    _ => return Err(format!("Unknown gender: {}", gender)),
+                })
+            }
+            "--weapon-slots" => search_args.weapon_slots = Some(parse_weapon_slots(&value()?)?),
+            "--sort" => {
+                let sort = value()?;
+                search_args.sort =
+                    Some(BuildSort::from_key(&sort).ok_or(format!("Unknown sort: {}", sort))?)
+            }
+            "--limit" => {
+                let limit = value()?;
+                search_args.limit = Some(
+                    limit
+                        .parse()
+                        .map_err(|_| format!("Invalid limit: {}", limit))?,
+                )
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    });
+
+    let args = match args {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            exit(2)
+        }
+    };
+    if args.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(err) = run(&args, search_args) {
+        eprintln!("{}", err);
+        exit(1)
+    }
+}
+
+// rab-cli serve [OPTIONS]
+fn serve() {
+    let mut port = server::DEFAULT_PORT;
+    let args = cli::parse_args_with(env::args().skip(2), |name, value| {
+        if name != "--port" {
+            return Ok(false);
+        }
+        let value = value()?;
+        port = value
+            .parse()
+            .map_err(|_| format!("Invalid port: {}", value))?;
+        Ok(true)
+    });
+    let args = match args {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, server::USAGE);
+            exit(2)
+        }
+    };
+    if args.help {
+        println!("{}", server::USAGE);
+        return;
+    }
+
+    if let Err(err) = server::serve(args.paths(), port, args.lang) {
+        eprintln!("{}", err);
+        exit(1)
+    }
+}
+
+fn run(args: &Args, search_args: SearchArgs) -> Result<(), String> {
+    let paths = args.paths();
+    let profile = get_profile(&paths.profile_file()).unwrap_or_default();
+
+    let locales = get_locales(&paths.locale_dir).unwrap_or_default();
+    let lang = args.lang.clone().unwrap_or(profile.lang);
+    *LOCALE.lock().unwrap() = locales.get(&lang).cloned();
+
+    let mut form = match &search_args.query {
+        Some(path) => read_query(path)?,
+        None => SearchForm::default(),
+    };
+    for (name, level, kind) in search_args.wishes {
+        form.wishes.push(FormWish {
+            skill: find_skill(&name)?,
+            level,
+            kind,
+            weight: 1,
+        })
+    }
+    if let Some(gender) = search_args.gender {
+        form.gender = gender;
+    }
+    if let Some(weapon_slots) = search_args.weapon_slots {
+        form.weapon_slots = weapon_slots;
+    }
+    if form.wishes.is_empty() {
+        return Err(format!("No wish given\n\n{}", USAGE));
+    }
+
+    let mut engine =
+        Engine::load(&paths).map_err(|err| format!("Can't read the armor files:\n{}", err))?;
+    engine.set_sort(search_args.sort.unwrap_or(profile.sort));
+
+    let mut builds = engine.search(&form);
+    if let Some(limit) = search_args.limit {
+        builds.truncate(limit);
+    }
+
+    if search_args.json {
+        let json = serde_json::to_string_pretty(&builds).map_err(|err| err.to_string())?;
+        println!("{}", json);
+    } else {
+        let optional_wishes = form.query(Default::default(), None).optional_wishes;
+        print_builds(&builds, &optional_wishes);
+    }
+
+    Ok(())
+}
+
+fn read_query(path: &Path) -> Result<SearchForm, String> {
+    let file =
+        File::open(path).map_err(|err| format!("Can't open {}:\n{}", path.display(), err))?;
+    let form = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_reader(file).map_err(|err| err.to_string())
+    } else {
+        ron::de::from_reader(file).map_err(|err| err.to_string())
+    };
+    form.map_err(|err| format!("Can't read the query {}:\n{}", path.display(), err))
+}
+
+// "AttackBoost=3" or "Attack Boost=3"
+fn parse_wish(wish: &str, kind: WishKind) -> Result<(String, u8, WishKind), String> {
+    let (name, level) = wish
+        .rsplit_once('=')
+        .ok_or(format!("Expected SKILL=LEVEL: {}", wish))?;
+    let level = level
+        .parse()
+        .map_err(|_| format!("Invalid level: {}", wish))?;
+    Ok((name.to_string(), level, kind))
+}
+
+fn parse_weapon_slots(slots: &str) -> Result<[u8; 3], String> {
+    let mut weapon_slots = [0; 3];
+    let sizes: Vec<&str> = slots.split(',').collect();
+    if sizes.len() > 3 {
+        return Err(format!("At most 3 weapon slots: {}", slots));
+    }
+    for (slot, size) in weapon_slots.iter_mut().zip(sizes) {
+        *slot = match size.trim().parse() {
+            Ok(size) if size <= 3 => size,
+            _ => return Err(format!("Invalid weapon slot: {}", size)),
+        }
+    }
+    Ok(weapon_slots)
+}
+
+// The names are compared without the case and the spaces.
+// The locale must be set before.
+fn find_skill(name: &str) -> Result<Skill, String> {
+    let simplify = |name: &str| -> String {
+        name.chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let name_simplified = simplify(name);
+    Skill::ALL
+        .iter()
+        .copied()
+        .find(|skill| {
+            simplify(&format!("{:?}", skill)) == name_simplified
+                || simplify(&LocalizedSkill(*skill).to_string()) == name_simplified
+        })
+        .ok_or(format!("Unknown skill: {}", name))
+}
+
+// the same text as the exported builds, with the optional wishes met
+fn print_builds(builds: &[Build], optional_wishes: &[(Skill, u8, u8)]) {
+    if builds.is_empty() {
+        println!("{}", InterfaceSymbol::NoResult);
+        return;
+    }
+
+    for (index, build) in builds.iter().enumerate() {
+        let name = format!("#{}", index + 1);
+        println!("{}", export_build(Some(&name), build, ExportFormat::Text));
+
+        if !optional_wishes.is_empty() {
+            let met = optional_wishes_met(build, optional_wishes);
+            if met.is_empty() {
+                println!("  {}", InterfaceSymbol::NoOptionalWishMet);
+            } else {
+                let met: Vec<String> = met
+                    .into_iter()
+                    .map(|skill| LocalizedSkill(skill).to_string())
+                    .collect();
+                println!(
+                    "  {}",
+                    InterfaceSymbol::TemplateOptionalWishesMet
+                        .to_string()
+                        .replace("{skills}", &met.join(", "))
+                );
+            }
+        }
+        println!();
+    }
+}
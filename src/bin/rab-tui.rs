@@ -0,0 +1,25 @@
+#[path = "../ui_tui.rs"]
+mod ui_tui;
+
+use std::{env, process::exit};
+
+use rab::cli;
+
+fn main() {
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, ui_tui::USAGE);
+            exit(2)
+        }
+    };
+    if args.help {
+        println!("{}", ui_tui::USAGE);
+        return;
+    }
+
+    if let Err(err) = ui_tui::run(args.paths(), args.lang) {
+        eprintln!("{}", err);
+        exit(1)
+    }
+}
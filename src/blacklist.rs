@@ -0,0 +1,111 @@
+use std::{
+    fs::{canonicalize, write, File},
+    path::Path,
+};
+
+use rab_core::armor_and_skills::Armor;
+use ron::{
+    de::from_reader,
+    ser::{to_string_pretty, PrettyConfig},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+
+// in the data directory
+pub const BLACKLIST_FILE: &str = "blacklist.ron";
+
+// Armors never given to the search engine
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Blacklist {
+    pub armors: Vec<String>,
+    pub families: Vec<String>,
+}
+
+impl Blacklist {
+    pub fn contains(&self, armor: &Armor) -> bool {
+        self.armors.contains(&armor.name)
+            || self
+                .families
+                .iter()
+                .any(|family| family == armor_family(&armor.name))
+    }
+
+    pub fn exclude_armor(&mut self, name: String) {
+        if !self.armors.contains(&name) {
+            self.armors.push(name);
+        }
+    }
+
+    pub fn exclude_family(&mut self, family: String) {
+        if !self.families.contains(&family) {
+            self.families.push(family);
+        }
+    }
+}
+
+// The pieces of a set share the first word of their name
+// e.g. "Rathalos Helm" and "Rathalos Mail"
+pub fn armor_family(name: &str) -> &str {
+    name.split_whitespace().next().unwrap_or(name)
+}
+
+pub fn get_blacklist(path: &Path) -> Result<Blacklist, Error> {
+    from_reader(File::open(path)?)
+}
+
+pub fn save_blacklist(blacklist: &Blacklist, path: &Path) -> Result<String, Error> {
+    let text = to_string_pretty(blacklist, PrettyConfig::new().indentor("  ".to_string()))?;
+
+    write(path, text)?;
+
+    let path = canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        paths::tests::unique_temp_dir,
+        search::{tests::armor, ArmorLists},
+    };
+
+    #[test]
+    fn excluded_pieces_and_families_are_not_searched() {
+        let mut blacklist = Blacklist::default();
+        blacklist.exclude_armor("Kamura Head Scarf".to_string());
+        blacklist.exclude_armor("Kamura Head Scarf".to_string());
+        blacklist.exclude_family("Rathalos".to_string());
+        assert_eq!(blacklist.armors.len(), 1);
+
+        let helmets = vec![
+            armor("Kamura Head Scarf", &[], &[]),
+            armor("Rathalos Helm", &[], &[]),
+            armor("Rathian Helm", &[], &[]),
+        ];
+        let lists = ArmorLists::new(
+            (helmets, vec![], vec![], vec![], vec![]),
+            vec![],
+            &blacklist,
+        );
+        let names: Vec<&str> = lists.helmets.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Rathian Helm"]);
+    }
+
+    #[test]
+    fn saved_blacklist_is_read_back() {
+        let dir = unique_temp_dir("blacklist");
+        let path = dir.join("blacklist.ron");
+        let blacklist = Blacklist {
+            armors: vec!["Rathian Helm".to_string()],
+            families: vec!["Kamura".to_string()],
+        };
+        save_blacklist(&blacklist, &path).unwrap();
+        let read = get_blacklist(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(read.armors, blacklist.armors);
+        assert_eq!(read.families, blacklist.families);
+    }
+}
@@ -0,0 +1,408 @@
+use std::{cmp::Reverse, collections::HashMap, fmt::Display};
+
+use rab_core::{
+    armor_and_skills::{Armor, Skill},
+    build_search::{Build, Jewels},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::locale::InterfaceSymbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorSlot {
+    Helmet,
+    Chest,
+    Arm,
+    Waist,
+    Leg,
+    Talisman,
+}
+
+impl ArmorSlot {
+    // same order as build_parts
+    pub const ALL: [ArmorSlot; 6] = [
+        ArmorSlot::Helmet,
+        ArmorSlot::Chest,
+        ArmorSlot::Arm,
+        ArmorSlot::Waist,
+        ArmorSlot::Leg,
+        ArmorSlot::Talisman,
+    ];
+
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn symbol(&self) -> InterfaceSymbol {
+        match self {
+            ArmorSlot::Helmet => InterfaceSymbol::Helmet,
+            ArmorSlot::Chest => InterfaceSymbol::Chest,
+            ArmorSlot::Arm => InterfaceSymbol::Arm,
+            ArmorSlot::Waist => InterfaceSymbol::Waist,
+            ArmorSlot::Leg => InterfaceSymbol::Leg,
+            ArmorSlot::Talisman => InterfaceSymbol::Talisman,
+        }
+    }
+}
+
+pub fn build_parts(build: &Build) -> [&Option<(Armor, Jewels)>; 6] {
+    [
+        &build.helmet,
+        &build.chest,
+        &build.arm,
+        &build.waist,
+        &build.leg,
+        &build.talisman,
+    ]
+}
+
+pub fn total_defense(build: &Build) -> u16 {
+    build_parts(build)
+        .into_iter()
+        .flatten()
+        .map(|(armor, _)| u16::from(armor.defense))
+        .sum()
+}
+
+// fire, water, thunder, ice, dragon
+pub fn total_resistances(build: &Build) -> [i16; 5] {
+    let mut resistances = [0; 5];
+    for (armor, _) in build_parts(build).into_iter().flatten() {
+        for (total, value) in resistances.iter_mut().zip([
+            armor.fire,
+            armor.water,
+            armor.thunder,
+            armor.ice,
+            armor.dragon,
+        ]) {
+            *total += i16::from(value);
+        }
+    }
+    resistances
+}
+
+// Associates each slot of the armor with the jewel placed on it (if any).
+// The slots are sorted by size.
+pub fn place_jewels(slots: &[u8], jewels: &Jewels) -> Vec<(u8, Option<Skill>)> {
+    let mut slots = slots.to_vec();
+    slots.sort_unstable();
+
+    let mut couple_slot_jewel = Vec::with_capacity(3);
+
+    let mut jewel_skills: Vec<Skill> = jewels.iter().copied().flatten().collect();
+    // reverse order
+    jewel_skills.sort_unstable_by_key(|a| Reverse(a.get_jewel_size()));
+
+    // to be sure that the jewel will be on the most little slot possible
+    let mut to_remove = None;
+    'slot_loop: for slot in slots {
+        if let Some(index) = to_remove {
+            jewel_skills.swap_remove(index);
+            to_remove = None;
+        }
+        for (index, skill) in jewel_skills.iter().enumerate() {
+            if skill.get_jewel_size().unwrap() <= slot {
+                couple_slot_jewel.push((slot, Some(*skill)));
+                to_remove = Some(index);
+                continue 'slot_loop;
+            }
+        }
+        couple_slot_jewel.push((slot, None));
+    }
+
+    couple_slot_jewel
+}
+
+// sizes of the armor and weapon slots without jewel
+pub fn free_slots(build: &Build, weapon_slots: &[u8; 3]) -> Vec<u8> {
+    build_parts(build)
+        .into_iter()
+        .flatten()
+        .flat_map(|(armor, jewels)| place_jewels(&armor.slots, jewels))
+        .chain(place_jewels(weapon_slots, &build.weapon_jewels))
+        // a weapon slot of size 0 is no slot
+        .filter(|(slot, skill)| *slot > 0 && skill.is_none())
+        .map(|(slot, _)| slot)
+        .collect()
+}
+
+// The required slots are counted by level (index 0 = level 1). A bigger
+// free slot can replace a smaller one, so the biggest requirements are
+// matched first, each with the smallest free slot that fits.
+pub fn has_free_slots(mut free_slots: Vec<u8>, required: [u8; 3]) -> bool {
+    free_slots.sort_unstable();
+    for (index, count) in required.iter().enumerate().rev() {
+        let size = index as u8 + 1;
+        for _ in 0..*count {
+            match free_slots.iter().position(|slot| *slot >= size) {
+                Some(position) => {
+                    free_slots.remove(position);
+                }
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+pub fn count_jewels<'a>(jewels: impl IntoIterator<Item = &'a Jewels>) -> HashMap<Skill, u8> {
+    let mut counts = HashMap::new();
+    for skill in jewels.into_iter().flatten().flatten() {
+        *counts.entry(*skill).or_insert(0) += 1;
+    }
+    counts
+}
+
+// jewels of the armors and the weapon
+pub fn build_jewel_counts(build: &Build) -> HashMap<Skill, u8> {
+    count_jewels(
+        build_parts(build)
+            .into_iter()
+            .flatten()
+            .map(|(_, jewels)| jewels)
+            .chain([&build.weapon_jewels]),
+    )
+}
+
+// skills with more jewels needed than owned
+pub fn missing_jewels(counts: &HashMap<Skill, u8>, inventory: &HashMap<Skill, u8>) -> Vec<Skill> {
+    counts
+        .iter()
+        .filter(|(skill, amount)| inventory.get(*skill).copied().unwrap_or(0) < **amount)
+        .map(|(skill, _)| *skill)
+        .collect()
+}
+
+// skills of the build that were not wished
+pub fn extra_skill_count(build: &Build, wishes: &[(Skill, u8)]) -> usize {
+    build
+        .get_all_skills_and_amounts()
+        .keys()
+        .filter(|skill| !wishes.iter().any(|(wish, _)| wish == *skill))
+        .count()
+}
+
+// pieces and jewels, two searches can find the same build
+pub type BuildKey = (Vec<Option<(String, Jewels)>>, Jewels);
+
+pub fn build_key(build: &Build) -> BuildKey {
+    (
+        build_parts(build)
+            .iter()
+            .map(|part| {
+                part.as_ref()
+                    .map(|(armor, jewels)| (armor.name.clone(), *jewels))
+            })
+            .collect(),
+        build.weapon_jewels,
+    )
+}
+
+// optional wishes are (skill, level, weight)
+pub fn optional_wishes_met(build: &Build, optional_wishes: &[(Skill, u8, u8)]) -> Vec<Skill> {
+    let skills = build.get_all_skills_and_amounts();
+    optional_wishes
+        .iter()
+        .filter(|(skill, level, _)| skills.get(skill).is_some_and(|amount| amount >= level))
+        .map(|(skill, _, _)| *skill)
+        .collect()
+}
+
+// the levels above the wished ones don't count
+pub fn optional_score(build: &Build, optional_wishes: &[(Skill, u8, u8)]) -> u32 {
+    let skills = build.get_all_skills_and_amounts();
+    optional_wishes
+        .iter()
+        .map(|(skill, level, weight)| {
+            let reached = skills.get(skill).copied().unwrap_or(0).min(*level);
+            u32::from(reached) * u32::from(*weight)
+        })
+        .sum()
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BuildSort {
+    #[default]
+    Defense,
+    Fire,
+    Water,
+    Thunder,
+    Ice,
+    Dragon,
+    FreeSlots,
+    ExtraSkills,
+}
+
+impl BuildSort {
+    pub const ALL: [BuildSort; 8] = [
+        BuildSort::Defense,
+        BuildSort::Fire,
+        BuildSort::Water,
+        BuildSort::Thunder,
+        BuildSort::Ice,
+        BuildSort::Dragon,
+        BuildSort::FreeSlots,
+        BuildSort::ExtraSkills,
+    ];
+
+    // value saved in the profiles before the version 1
+    pub fn key(&self) -> &'static str {
+        match self {
+            BuildSort::Defense => "defense",
+            BuildSort::Fire => "fire",
+            BuildSort::Water => "water",
+            BuildSort::Thunder => "thunder",
+            BuildSort::Ice => "ice",
+            BuildSort::Dragon => "dragon",
+            BuildSort::FreeSlots => "free_slots",
+            BuildSort::ExtraSkills => "extra_skills",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|sort| sort.key() == key)
+    }
+}
+
+impl Display for BuildSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BuildSort::Defense => InterfaceSymbol::Defense,
+                BuildSort::Fire => InterfaceSymbol::Fire,
+                BuildSort::Water => InterfaceSymbol::Water,
+                BuildSort::Thunder => InterfaceSymbol::Thunder,
+                BuildSort::Ice => InterfaceSymbol::Ice,
+                BuildSort::Dragon => InterfaceSymbol::Dragon,
+                BuildSort::FreeSlots => InterfaceSymbol::FreeSlots,
+                BuildSort::ExtraSkills => InterfaceSymbol::ExtraSkills,
+            }
+        )
+    }
+}
+
+// The best builds first: the weighted optional wishes, then the chosen sort.
+// The sort is stable so the search order is kept between equivalent builds.
+pub fn sort_builds(
+    builds: &mut [Build],
+    sort: BuildSort,
+    wishes: &[(Skill, u8)],
+    optional_wishes: &[(Skill, u8, u8)],
+    weapon_slots: &[u8; 3],
+) {
+    match sort {
+        BuildSort::Defense => builds.sort_by_key(|build| Reverse(total_defense(build))),
+        BuildSort::Fire => builds.sort_by_key(|build| Reverse(total_resistances(build)[0])),
+        BuildSort::Water => builds.sort_by_key(|build| Reverse(total_resistances(build)[1])),
+        BuildSort::Thunder => builds.sort_by_key(|build| Reverse(total_resistances(build)[2])),
+        BuildSort::Ice => builds.sort_by_key(|build| Reverse(total_resistances(build)[3])),
+        BuildSort::Dragon => builds.sort_by_key(|build| Reverse(total_resistances(build)[4])),
+        BuildSort::FreeSlots => builds.sort_by_cached_key(|build| {
+            let slots = free_slots(build, weapon_slots);
+            Reverse((
+                slots.iter().map(|slot| u16::from(*slot)).sum::<u16>(),
+                slots.len(),
+            ))
+        }),
+        BuildSort::ExtraSkills => {
+            // the optional wishes are not extra skills
+            let all_wishes: Vec<(Skill, u8)> = wishes
+                .iter()
+                .copied()
+                .chain(
+                    optional_wishes
+                        .iter()
+                        .map(|(skill, level, _)| (*skill, *level)),
+                )
+                .collect();
+            builds.sort_by_cached_key(|build| Reverse(extra_skill_count(build, &all_wishes)))
+        }
+    }
+    if !optional_wishes.is_empty() {
+        builds.sort_by_cached_key(|build| Reverse(optional_score(build, optional_wishes)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::armor;
+
+    fn build(helmet: Armor) -> Build {
+        Build {
+            helmet: Some((helmet, [None; 3])),
+            chest: None,
+            arm: None,
+            waist: None,
+            leg: None,
+            talisman: None,
+            weapon_jewels: [None; 3],
+        }
+    }
+
+    fn names(builds: &[Build]) -> Vec<&str> {
+        builds
+            .iter()
+            .map(|build| build.helmet.as_ref().unwrap().0.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_defense_then_by_resistance() {
+        let mut low = armor("low", &[], &[]);
+        low.defense = 10;
+        low.fire = 3;
+        let mut high = armor("high", &[], &[]);
+        high.defense = 50;
+        high.fire = -2;
+
+        let mut builds = vec![build(low), build(high)];
+        sort_builds(&mut builds, BuildSort::Defense, &[], &[], &[0; 3]);
+        assert_eq!(names(&builds), ["high", "low"]);
+        sort_builds(&mut builds, BuildSort::Fire, &[], &[], &[0; 3]);
+        assert_eq!(names(&builds), ["low", "high"]);
+    }
+
+    #[test]
+    fn sorts_by_free_slots_and_extra_skills() {
+        let slotted = armor("slotted", &[], &[2, 1]);
+        let skilled = armor(
+            "skilled",
+            &[(Skill::AttackBoost, 1), (Skill::Botanist, 1)],
+            &[1],
+        );
+
+        let mut builds = vec![build(skilled.clone()), build(slotted.clone())];
+        sort_builds(&mut builds, BuildSort::FreeSlots, &[], &[], &[0; 3]);
+        assert_eq!(names(&builds), ["slotted", "skilled"]);
+
+        // the wished skill is not an extra skill
+        let wishes = [(Skill::AttackBoost, 1)];
+        let mut builds = vec![build(slotted), build(skilled)];
+        sort_builds(&mut builds, BuildSort::ExtraSkills, &wishes, &[], &[0; 3]);
+        assert_eq!(names(&builds), ["skilled", "slotted"]);
+        assert_eq!(extra_skill_count(&builds[0], &wishes), 1);
+    }
+
+    #[test]
+    fn free_slots_count_the_weapon_slots() {
+        let mut with_jewels = build(armor("helmet", &[], &[2, 1]));
+        with_jewels.helmet.as_mut().unwrap().1 = [Some(Skill::AttackBoost), None, None];
+        with_jewels.weapon_jewels = [Some(Skill::Botanist), None, None];
+
+        // a weapon slot of size 0 is no slot
+        assert_eq!(free_slots(&with_jewels, &[1, 3, 0]), [1, 3]);
+        assert!(has_free_slots(
+            free_slots(&with_jewels, &[1, 3, 0]),
+            [1, 0, 1]
+        ));
+
+        let plain = build(armor("plain", &[], &[1]));
+        let mut builds = vec![plain, with_jewels];
+        sort_builds(&mut builds, BuildSort::FreeSlots, &[], &[], &[1, 3, 0]);
+        assert_eq!(names(&builds), ["plain", "helmet"]);
+    }
+}
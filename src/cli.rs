@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use crate::{paths::Paths, profile::Theme};
+
+pub const USAGE: &str = "Usage: rab [OPTIONS]
+
+Options:
+  --data-dir <DIR>     Directory of every RAB file (profile included)
+  --armors-dir <DIR>   Directory of the armor files
+  --locale-dir <DIR>   Directory of the locale files
+  --lang <NAME>        Language for this launch, like English
+  --theme <THEME>      dark or light for this launch
+  --offline            No armor update or download
+  -h, --help           Print this help";
+
+// The language and the theme given here are not saved in the profile.
+#[derive(Default)]
+pub struct Args {
+    pub data_dir: Option<PathBuf>,
+    pub armors_dir: Option<PathBuf>,
+    pub locale_dir: Option<PathBuf>,
+    pub lang: Option<String>,
+    pub theme: Option<Theme>,
+    pub offline: bool,
+    pub help: bool,
+}
+
+impl Args {
+    // The default directories are only resolved (and migrated)
+    // if no data directory is given.
+    pub fn paths(&self) -> Paths {
+        let mut paths = match &self.data_dir {
+            Some(dir) => {
+                let paths = Paths::in_dirs(dir.clone(), dir.clone());
+                paths.create_dirs();
+                paths
+            }
+            None => Paths::resolve(),
+        };
+        if let Some(dir) = &self.armors_dir {
+            paths.armors_dir = dir.clone();
+        }
+        if let Some(dir) = &self.locale_dir {
+            paths.locale_dir = dir.clone();
+        }
+        paths
+    }
+}
+
+// The values can be given as "--lang English" or "--lang=English".
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    parse_args_with(args, |_, _| Ok(false))
+}
+
+// The arguments unknown to Args are given to `extra` with a function to get
+// their value. `extra` returns false if it doesn't know the argument either.
+pub fn parse_args_with(
+    args: impl IntoIterator<Item = String>,
+    mut extra: impl FnMut(&str, &mut dyn FnMut() -> Result<String, String>) -> Result<bool, String>,
+) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // only a long option can have an inline value
+        let inline = match arg.strip_prefix("--") {
+            Some(option) => option.split_once('='),
+            None => None,
+        };
+        let (name, mut inline_value) = match inline {
+            Some((name, value)) => (format!("--{}", name), Some(value.to_string())),
+            None => (arg, None),
+        };
+        let mut value = || match inline_value.take().or_else(|| args.next()) {
+            Some(value) => Ok(value),
+            None => Err(format!("Missing value for {}", name)),
+        };
+
+        match name.as_str() {
+            "--offline" => parsed.offline = true,
+            "-h" | "--help" => parsed.help = true,
+            "--data-dir" => parsed.data_dir = Some(value()?.into()),
+            "--armors-dir" => parsed.armors_dir = Some(value()?.into()),
+            "--locale-dir" => parsed.locale_dir = Some(value()?.into()),
+            "--lang" => parsed.lang = Some(value()?),
+            "--theme" => {
+                let theme = value()?;
+                parsed.theme = Some(match theme.as_str() {
+                    "dark" => Theme::Dark,
+                    "light" => Theme::Light,
+                    _ => return Err(format!("Unknown theme: {}", theme)),
+                })
+            }
+            _ => {
+                if !extra(&name, &mut value)? {
+                    return Err(format!("Unknown argument: {}", name));
+                }
+            }
+        }
+        // the inline value of a flag is not read above
+        if inline_value.is_some() {
+            return Err(format!("{} takes no value", name));
+        }
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn values_can_follow_or_be_inline() {
+        let parsed = parse_args(args(&[
+            "--lang",
+            "English",
+            "--theme=light",
+            "--data-dir=/tmp/rab",
+            "--locale-dir",
+            "/tmp/a=b",
+            "--offline",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.lang.as_deref(), Some("English"));
+        assert!(parsed.theme == Some(Theme::Light));
+        assert_eq!(parsed.data_dir, Some(PathBuf::from("/tmp/rab")));
+        assert_eq!(parsed.locale_dir, Some(PathBuf::from("/tmp/a=b")));
+        assert!(parsed.offline);
+        assert!(!parsed.help);
+    }
+
+    #[test]
+    fn wrong_arguments_are_rejected() {
+        assert!(parse_args(args(&["--lang"])).is_err());
+        assert!(parse_args(args(&["--theme", "blue"])).is_err());
+        assert!(parse_args(args(&["--unknown"])).is_err());
+        assert!(parse_args(args(&["--offline=false"])).is_err());
+        assert!(parse_args(args(&["-h=1"])).is_err());
+    }
+
+    #[test]
+    fn extra_arguments_are_given_to_the_caller() {
+        let mut port = None;
+        let parsed = parse_args_with(args(&["--port", "8080", "-h"]), |name, value| {
+            if name != "--port" {
+                return Ok(false);
+            }
+            port = Some(value()?);
+            Ok(true)
+        })
+        .unwrap();
+        assert_eq!(port.as_deref(), Some("8080"));
+        assert!(parsed.help);
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use rab_core::armor_and_skills::{Armor, Skill};
+
+use crate::{
+    build_stats::ArmorSlot,
+    search::{run_in_background, SearchJob},
+};
+
+// Why a search didn't find any build
+#[derive(Debug, Clone)]
+pub struct Diagnosis {
+    // wished skill, wished level, highest level reachable without the other wishes
+    pub max_levels_alone: Vec<(Skill, u8, u8)>,
+    // None if the wishes together are reachable (the other requirements are the problem)
+    pub minimal_conflict: Option<Vec<(Skill, u8)>>,
+    // the slots that fit every wished jewel and if they would give builds,
+    // None if the weapon slots are already big enough
+    pub better_weapon_slots: Option<([u8; 3], bool)>,
+    // a talisman with this skill and level would give builds
+    pub helpful_talisman: Option<(Skill, u8)>,
+}
+
+pub async fn run_diagnosis(job: Arc<SearchJob>) -> Option<Diagnosis> {
+    run_in_background(move || diagnose(&job)).await.flatten()
+}
+
+// None if the job has been cancelled
+fn diagnose(job: &SearchJob) -> Option<Diagnosis> {
+    let wishes = &job.query().wishes;
+
+    let mut max_levels_alone = Vec::with_capacity(wishes.len());
+    for (skill, level) in wishes.iter() {
+        max_levels_alone.push((*skill, *level, job.max_level(&[], *skill, 0)?));
+    }
+
+    let minimal_conflict = minimal_conflict(job, &max_levels_alone)?;
+
+    // three slots of the biggest wished jewel
+    let biggest_jewel = wishes
+        .iter()
+        .filter_map(|(skill, _)| skill.get_jewel_size())
+        .max()
+        .unwrap_or(0);
+    let better_weapon_slots = if job
+        .query()
+        .weapon_slots
+        .iter()
+        .all(|slot| *slot >= biggest_jewel)
+    {
+        None
+    } else {
+        let mut query = job.query().clone();
+        query.weapon_slots = [biggest_jewel; 3];
+        let help = SearchJob::new(query.clone(), job.armors().clone()).has_builds(wishes);
+        Some((query.weapon_slots, help))
+    };
+
+    // the talismans can't change anything if the talisman slot is locked
+    let mut helpful_talisman = None;
+    if job.query().locks[ArmorSlot::Talisman.index()].is_none() {
+        // a talisman with the wished level of one of the wishes
+        let candidates = minimal_conflict.as_ref().unwrap_or(wishes);
+        for (skill, level) in candidates.iter() {
+            if job.is_cancelled() {
+                return None;
+            }
+            let mut armors = job.armors().clone();
+            armors.talismans.push(Armor {
+                name: "Diagnosis talisman".to_string(),
+                skills: vec![(*skill, *level)],
+                ..Default::default()
+            });
+            if SearchJob::new(job.query().clone(), armors).has_builds(wishes) {
+                helpful_talisman = Some((*skill, *level));
+                break;
+            }
+        }
+    }
+
+    Some(Diagnosis {
+        max_levels_alone,
+        minimal_conflict,
+        better_weapon_slots,
+        helpful_talisman,
+    })
+}
+
+// The wishes are removed one at a time while the rest still gives no build,
+// so there is one search per wish. What is left is a conflict where every
+// wish is needed, not always the smallest one.
+// Outer None if the job has been cancelled.
+fn minimal_conflict(
+    job: &SearchJob,
+    max_levels_alone: &[(Skill, u8, u8)],
+) -> Option<Option<Vec<(Skill, u8)>>> {
+    // a single wish can be the problem, no need to search again
+    if let Some((skill, level, _)) = max_levels_alone
+        .iter()
+        .find(|(_, level, max_level)| level > max_level)
+    {
+        return Some(Some(vec![(*skill, *level)]));
+    }
+
+    let mut conflict = job.query().wishes.clone();
+    if job.has_builds(&conflict) {
+        return Some(None);
+    }
+
+    let mut index = 0;
+    while index < conflict.len() {
+        if job.is_cancelled() {
+            return None;
+        }
+        let mut smaller = conflict.clone();
+        smaller.remove(index);
+        if job.has_builds(&smaller) {
+            index += 1;
+        } else {
+            conflict = smaller;
+        }
+    }
+
+    Some(Some(conflict))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::{armor, lists, query};
+
+    #[test]
+    fn the_conflict_keeps_only_the_needed_wishes() {
+        let armors = lists(
+            vec![
+                armor("h1", &[(Skill::AttackBoost, 1)], &[]),
+                armor("h2", &[(Skill::CriticalEye, 1)], &[]),
+            ],
+            vec![armor("c", &[(Skill::Botanist, 1)], &[])],
+            vec![],
+        );
+        let job = SearchJob::new(
+            query(&[
+                (Skill::AttackBoost, 1),
+                (Skill::Botanist, 1),
+                (Skill::CriticalEye, 1),
+            ]),
+            armors,
+        );
+        let max_levels_alone = [
+            (Skill::AttackBoost, 1, 1),
+            (Skill::Botanist, 1, 1),
+            (Skill::CriticalEye, 1, 1),
+        ];
+
+        assert_eq!(
+            minimal_conflict(&job, &max_levels_alone),
+            Some(Some(vec![(Skill::AttackBoost, 1), (Skill::CriticalEye, 1)]))
+        );
+    }
+
+    #[test]
+    fn the_weapon_slots_and_the_talisman_come_from_the_wishes() {
+        let job = SearchJob::new(
+            query(&[(Skill::Botanist, 2)]),
+            lists(vec![], vec![], vec![]),
+        );
+
+        let diagnosis = diagnose(&job).unwrap();
+        assert_eq!(diagnosis.minimal_conflict, Some(vec![(Skill::Botanist, 2)]));
+        // the botanist jewels have the size 1
+        assert_eq!(diagnosis.better_weapon_slots, Some(([1, 1, 1], true)));
+        assert_eq!(diagnosis.helpful_talisman, Some((Skill::Botanist, 2)));
+    }
+}
@@ -0,0 +1,237 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fs::{canonicalize, write},
+    io::Error,
+    path::Path,
+};
+
+use lexical_sort::natural_lexical_cmp;
+use rab_core::{
+    armor_and_skills::Skill,
+    build_search::{Build, Jewels},
+};
+
+use crate::{
+    build_stats::{build_parts, place_jewels, total_defense, total_resistances, ArmorSlot},
+    locale::{InterfaceSymbol, LocalizedArmor, LocalizedSkill},
+};
+
+// The Markdown one is for Discord and the like, so lists
+// and bold text only, the tables are not rendered there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Text,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Text => "txt",
+        }
+    }
+
+    fn title(&self, name: &str) -> String {
+        match self {
+            ExportFormat::Markdown => format!("## {}", markdown_escape(name)),
+            ExportFormat::Text => name.to_string(),
+        }
+    }
+
+    fn line(&self, label: impl ToString, value: &str) -> String {
+        match self {
+            ExportFormat::Markdown => format!(
+                "- **{}**: {}",
+                markdown_escape(&label.to_string()),
+                markdown_escape(value)
+            ),
+            ExportFormat::Text => format!("  {}: {}", label.to_string(), value),
+        }
+    }
+}
+
+// The build names, the armors and the localized texts can have
+// characters read as formatting, like the underscores.
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '|' | '*' | '_' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+pub fn export_build(name: Option<&str>, build: &Build, format: ExportFormat) -> String {
+    let mut lines = Vec::new();
+    if let Some(name) = name {
+        lines.push(format.title(name));
+    }
+
+    for (slot, part) in ArmorSlot::ALL.iter().zip(build_parts(build)) {
+        let piece = match part {
+            Some((armor, jewels)) => {
+                let jewels = jewels_text(&armor.slots, jewels);
+                if jewels.is_empty() {
+                    LocalizedArmor(armor).to_string()
+                } else {
+                    format!("{} ({})", LocalizedArmor(armor), jewels)
+                }
+            }
+            None => InterfaceSymbol::Free.to_string(),
+        };
+        lines.push(format.line(slot.symbol(), &piece));
+    }
+
+    if build.weapon_jewels.iter().any(Option::is_some) {
+        let skills: Vec<String> = build
+            .weapon_jewels
+            .iter()
+            .flatten()
+            .map(|skill| LocalizedSkill(*skill).to_string())
+            .collect();
+        lines.push(format.line(InterfaceSymbol::WeaponSlots, &skills.join(", ")));
+    }
+
+    let mut skills: Vec<(Skill, u8)> = build.get_all_skills_and_amounts().drain().collect();
+    skills.sort_unstable_by_key(|(_, amount)| Reverse(*amount));
+    let skills: Vec<String> = skills
+        .into_iter()
+        .map(|(skill, amount)| format!("{} {}", LocalizedSkill(skill), amount))
+        .collect();
+    lines.push(format.line(InterfaceSymbol::Skills, &skills.join(", ")));
+
+    lines.push(format.line(InterfaceSymbol::Defense, &total_defense(build).to_string()));
+    let resistances: Vec<String> = [
+        InterfaceSymbol::Fire,
+        InterfaceSymbol::Water,
+        InterfaceSymbol::Thunder,
+        InterfaceSymbol::Ice,
+        InterfaceSymbol::Dragon,
+    ]
+    .into_iter()
+    .zip(total_resistances(build))
+    .map(|(symbol, value)| format!("{} {}", symbol, value))
+    .collect();
+    lines.push(format.line(InterfaceSymbol::Resistances, &resistances.join(", ")));
+
+    lines.join("\n")
+}
+
+// sorted by name like in the builds page
+pub fn export_builds(builds: &HashMap<String, Build>, format: ExportFormat) -> String {
+    let mut names: Vec<&String> = builds.keys().collect();
+    names.sort_unstable_by(|a, b| natural_lexical_cmp(a, b));
+    names
+        .into_iter()
+        .map(|name| export_build(Some(name.as_str()), &builds[name], format))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn save_export(text: &str, path: &Path) -> Result<String, Error> {
+    write(path, text)?;
+
+    let path = canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// The text of a slot with its jewel, the same in every interface
+pub fn slot_text(size: u8, skill: Option<Skill>) -> String {
+    match skill {
+        Some(skill) => InterfaceSymbol::TemplateJewelOnSlot
+            .to_string()
+            .replace("{skill}", &LocalizedSkill(skill).to_string())
+            .replace("{size}", &size.to_string()),
+        None => InterfaceSymbol::TemplateFreeSlot
+            .to_string()
+            .replace("{size}", &size.to_string()),
+    }
+}
+
+fn jewels_text(slots: &[u8], jewels: &Jewels) -> String {
+    place_jewels(slots, jewels)
+        .into_iter()
+        .map(|(size, skill)| slot_text(size, skill))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::armor;
+
+    fn build(name: &str) -> Build {
+        let mut helmet = armor(name, &[(Skill::AttackBoost, 1)], &[2, 1]);
+        helmet.defense = 10;
+        Build {
+            helmet: Some((helmet, [Some(Skill::CriticalEye), None, None])),
+            chest: None,
+            arm: None,
+            waist: None,
+            leg: None,
+            talisman: None,
+            weapon_jewels: [Some(Skill::Botanist), None, None],
+        }
+    }
+
+    #[test]
+    fn slots_are_shown_with_their_jewel() {
+        assert_eq!(slot_text(1, None), "Free lvl 1 slot");
+        assert_eq!(
+            slot_text(2, Some(Skill::CriticalEye)),
+            "CriticalEye on lvl 2 slot"
+        );
+    }
+
+    #[test]
+    fn build_is_exported_in_each_format() {
+        let text = export_build(Some("Mine"), &build("Helm"), ExportFormat::Text);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "Mine");
+        assert_eq!(
+            lines[1],
+            "  Helmet: Helm (Free lvl 1 slot, CriticalEye on lvl 2 slot)"
+        );
+        assert_eq!(lines[2], "  Chest: Free");
+        assert!(lines.contains(&"  Weapon slots: Botanist"));
+        assert!(lines.contains(&"  Defense: 10"));
+
+        let markdown = export_build(Some("Mine"), &build("Helm"), ExportFormat::Markdown);
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines[0], "## Mine");
+        assert_eq!(lines[2], "- **Chest**: Free");
+    }
+
+    #[test]
+    fn markdown_characters_are_escaped() {
+        let markdown = export_build(
+            Some("my_build*"),
+            &build("Helm|`1`"),
+            ExportFormat::Markdown,
+        );
+        let lines: Vec<&str> = markdown.lines().collect();
+        assert_eq!(lines[0], r"## my\_build\*");
+        assert!(lines[1].starts_with(r"- **Helmet**: Helm\|\`1\` ("));
+
+        // the text is kept as is
+        let text = export_build(Some("my_build*"), &build("Helm"), ExportFormat::Text);
+        assert!(text.starts_with("my_build*\n"));
+    }
+
+    #[test]
+    fn builds_are_exported_by_name() {
+        let builds = HashMap::from([
+            ("build 10".to_string(), build("Helm")),
+            ("build 2".to_string(), build("Helm")),
+        ]);
+        let text = export_builds(&builds, ExportFormat::Markdown);
+        let titles: Vec<&str> = text.lines().filter(|line| line.starts_with("##")).collect();
+        assert_eq!(titles, ["## build 2", "## build 10"]);
+    }
+}
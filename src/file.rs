@@ -1,4 +1,8 @@
-use std::fs::{self, File};
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::Path,
+};
 
 use ron::{
     de::from_reader,
@@ -6,9 +10,32 @@ use ron::{
     Error,
 };
 
-use rab_core::armor_and_skills::{armor_to_talisman, talisman_to_armor, Armor, Talisman};
+use rab_core::{
+    armor_and_skills::{armor_to_talisman, talisman_to_armor, Armor, Skill, Talisman},
+    build_search::Build,
+};
+
+use crate::paths::{Paths, ARMOR_FILES};
+
+// in the data directory
+pub const TALISMANS_FILE: &str = "talismans.ron";
+pub const BUILDS_FILE: &str = "builds.ron";
+pub const JEWELS_FILE: &str = "jewels.ron";
+
+pub type AllArmors = (Vec<Armor>, Vec<Armor>, Vec<Armor>, Vec<Armor>, Vec<Armor>);
+
+pub fn get_all_armors_from_file(paths: &Paths) -> Result<AllArmors, Error> {
+    let [helmets, chests, arms, waists, legs] = ARMOR_FILES.map(|file| paths.armor_file(file));
+    Ok((
+        get_armor_list(&helmets)?,
+        get_armor_list(&chests)?,
+        get_armor_list(&arms)?,
+        get_armor_list(&waists)?,
+        get_armor_list(&legs)?,
+    ))
+}
 
-pub fn save_talismans_to_file(talismans: &[Armor], path: &str) -> Result<String, Error> {
+pub fn save_talismans_to_file(talismans: &[Armor], path: &Path) -> Result<String, Error> {
     let talismans: Vec<Talisman> = talismans.iter().map(armor_to_talisman).collect();
 
     let text = to_string_pretty(&talismans, PrettyConfig::new().indentor("  ".to_string()))?;
@@ -20,14 +47,43 @@ pub fn save_talismans_to_file(talismans: &[Armor], path: &str) -> Result<String,
     Ok(path.to_string_lossy().into_owned())
 }
 
-pub fn get_armor_list(path: &str) -> Result<Vec<Armor>, Error> {
+pub fn get_armor_list(path: &Path) -> Result<Vec<Armor>, Error> {
     let armors: Vec<Armor> = from_reader(File::open(path)?)?;
     Ok(armors)
 }
 
-pub fn get_talismans(path: &str) -> Result<Vec<Armor>, Error> {
+pub fn get_talismans(path: &Path) -> Result<Vec<Armor>, Error> {
     let file = File::open(path)?;
     let talismans: Vec<Talisman> = from_reader(file)?;
     let talismans: Vec<Armor> = talismans.iter().map(talisman_to_armor).collect();
     Ok(talismans)
 }
+
+pub fn save_builds(builds: &HashMap<String, Build>, path: &Path) -> Result<String, Error> {
+    let text = to_string_pretty(builds, PrettyConfig::new().indentor("  ".to_string()))?;
+
+    fs::write(path, text)?;
+
+    let path = fs::canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+pub fn get_saved_builds(path: &Path) -> Result<HashMap<String, Build>, Error> {
+    from_reader(File::open(path)?)
+}
+
+// amount of owned jewels for each skill
+pub fn get_jewel_inventory(path: &Path) -> Result<HashMap<Skill, u8>, Error> {
+    from_reader(File::open(path)?)
+}
+
+pub fn save_jewel_inventory(inventory: &HashMap<Skill, u8>, path: &Path) -> Result<String, Error> {
+    let text = to_string_pretty(inventory, PrettyConfig::new().indentor("  ".to_string()))?;
+
+    fs::write(path, text)?;
+
+    let path = fs::canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
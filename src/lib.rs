@@ -0,0 +1,28 @@
+pub mod api;
+pub mod app_state;
+pub mod blacklist;
+pub mod build_stats;
+pub mod cli;
+pub mod diagnosis;
+pub mod export;
+pub mod file;
+pub mod locale;
+pub mod paths;
+pub mod profile;
+pub mod search;
+pub mod search_form;
+pub mod server;
+pub mod session;
+pub mod share;
+pub mod update;
+
+pub use api::Engine;
+
+use std::sync::Mutex;
+
+use locale::Locale;
+use once_cell::sync::Lazy;
+
+// I use this global variable to use the locale within the implementations of the Display trait
+// If you have another solution don't hesitate to tell me.
+pub static LOCALE: Lazy<Mutex<Option<Locale>>> = Lazy::new(Default::default);
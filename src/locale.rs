@@ -2,7 +2,7 @@ use ron::{de::from_reader, Error};
 use serde::Deserialize;
 use std::ops::Deref;
 use std::{collections::HashMap, fs::File};
-use std::{fmt::Display, fs};
+use std::{fmt::Display, fs, path::Path};
 
 use rab_core::armor_and_skills::{Armor, Skill};
 
@@ -14,7 +14,7 @@ pub struct Locale {
     armors: HashMap<String, String>,
 }
 
-pub fn get_locales(directory_path: &str) -> Result<HashMap<String, Locale>, Error> {
+pub fn get_locales(directory_path: &Path) -> Result<HashMap<String, Locale>, Error> {
     let mut locales: HashMap<String, Locale> = HashMap::with_capacity(7);
     let paths = fs::read_dir(directory_path)?;
 
@@ -106,6 +106,61 @@ pub enum InterfaceSymbol {
     ManageBuilds,
     SaveBuild,
     NewBuildName,
+    TemplateBuildsFoundSoFar,
+    SortBy,
+    FreeSlots,
+    ExtraSkills,
+    Minimums,
+    Lock,
+    Unlock,
+    LockedPieces,
+    ManageExclusions,
+    Exclude,
+    ExcludeFamily,
+    ExcludedPieces,
+    ExcludedFamilies,
+    ManageJewels,
+    IgnoreJewelInventory,
+    AdditionalSkills,
+    Diagnose,
+    Diagnosing,
+    MaxLevelsAlone,
+    TemplateLevelAlone,
+    MinimalConflict,
+    NoWishConflict,
+    TemplateBetterWeaponSlotsHelp,
+    TemplateBetterWeaponSlotsDontHelp,
+    TemplateHelpfulTalisman,
+    NoHelpfulTalisman,
+    TemplateMaxLevel,
+    Required,
+    Optional,
+    Avoid,
+    HighestAllowedLevel,
+    TemplateSlotLevel,
+    Presets,
+    PresetName,
+    RestoreResults,
+    OfflineMode,
+    Rename,
+    Weight,
+    TemplateOptionalWishesMet,
+    NoOptionalWishMet,
+    CopyShareCode,
+    ImportFromCode,
+    ShareCode,
+    InvalidShareCode,
+    TemplateUnknownShareCodeVersion,
+    TemplateUnknownPieces,
+    TemplateUnknownSkill,
+    QueryCode,
+    CopyQueryCode,
+    Skills,
+    Resistances,
+    CopyAsMarkdown,
+    CopyAsText,
+    ExportAsMarkdown,
+    ExportAsText,
 }
 
 impl InterfaceSymbol {
@@ -113,6 +168,72 @@ impl InterfaceSymbol {
         match self {
             InterfaceSymbol::TemplateFreeSlot => "Free lvl {size} slot".to_string(),
             InterfaceSymbol::TemplateJewelOnSlot => "{skill} on lvl {size} slot".to_string(),
+            InterfaceSymbol::TemplateBuildsFoundSoFar => "{count} builds found so far".to_string(),
+            InterfaceSymbol::WeaponSlots => "Weapon slots".to_string(),
+            InterfaceSymbol::SortBy => "Sort by".to_string(),
+            InterfaceSymbol::FreeSlots => "Free slots".to_string(),
+            InterfaceSymbol::ExtraSkills => "Extra skills".to_string(),
+            InterfaceSymbol::Lock => "Lock".to_string(),
+            InterfaceSymbol::Unlock => "Unlock".to_string(),
+            InterfaceSymbol::LockedPieces => "Locked pieces".to_string(),
+            InterfaceSymbol::ManageExclusions => "Manage exclusions".to_string(),
+            InterfaceSymbol::ExcludeFamily => "Exclude the family".to_string(),
+            InterfaceSymbol::ExcludedPieces => "Excluded pieces".to_string(),
+            InterfaceSymbol::ExcludedFamilies => "Excluded families".to_string(),
+            InterfaceSymbol::ManageJewels => "Manage jewels".to_string(),
+            InterfaceSymbol::IgnoreJewelInventory => "Ignore the jewel inventory".to_string(),
+            InterfaceSymbol::AdditionalSkills => "Additional skills".to_string(),
+            InterfaceSymbol::Diagnose => "Why no build?".to_string(),
+            InterfaceSymbol::Diagnosing => "Looking for the cause...".to_string(),
+            InterfaceSymbol::TemplateMaxLevel => "max {level}".to_string(),
+            InterfaceSymbol::TemplateOptionalWishesMet => "Optional wishes met: {skills}".to_string(),
+            InterfaceSymbol::NoOptionalWishMet => "No optional wish met".to_string(),
+            InterfaceSymbol::HighestAllowedLevel => "Highest allowed level".to_string(),
+            InterfaceSymbol::TemplateSlotLevel => "lvl {size}".to_string(),
+            InterfaceSymbol::PresetName => "Preset name".to_string(),
+            InterfaceSymbol::OfflineMode => "Offline mode".to_string(),
+            InterfaceSymbol::RestoreResults => "Restore the last results at startup".to_string(),
+            InterfaceSymbol::MaxLevelsAlone => {
+                "Highest level of each wish without the other wishes".to_string()
+            }
+            InterfaceSymbol::TemplateLevelAlone => {
+                "{skill}: lvl {max} reachable, lvl {wished} wished".to_string()
+            }
+            InterfaceSymbol::MinimalConflict => {
+                "Conflicting wishes, without any one of them the others give builds".to_string()
+            }
+            InterfaceSymbol::NoWishConflict => {
+                "The wishes are compatible, the other requirements prevent the builds".to_string()
+            }
+            InterfaceSymbol::TemplateBetterWeaponSlotsHelp => {
+                "A weapon with {slots} slots would give builds".to_string()
+            }
+            InterfaceSymbol::TemplateBetterWeaponSlotsDontHelp => {
+                "A weapon with {slots} slots would not give builds".to_string()
+            }
+            InterfaceSymbol::TemplateHelpfulTalisman => {
+                "A talisman with {skill} lvl {level} would give builds".to_string()
+            }
+            InterfaceSymbol::NoHelpfulTalisman => {
+                "A talisman with one of the wishes would not give builds".to_string()
+            }
+            InterfaceSymbol::CopyShareCode => "Copy share code".to_string(),
+            InterfaceSymbol::ImportFromCode => "Import from code".to_string(),
+            InterfaceSymbol::ShareCode => "Share code".to_string(),
+            InterfaceSymbol::InvalidShareCode => "Invalid share code".to_string(),
+            InterfaceSymbol::TemplateUnknownShareCodeVersion => {
+                "Share code of version {version}, update RAB to read it".to_string()
+            }
+            InterfaceSymbol::TemplateUnknownPieces => {
+                "Unknown pieces, update the armors: {pieces}".to_string()
+            }
+            InterfaceSymbol::TemplateUnknownSkill => "Unknown skill: {skill}".to_string(),
+            InterfaceSymbol::QueryCode => "Query code".to_string(),
+            InterfaceSymbol::CopyQueryCode => "Copy query code".to_string(),
+            InterfaceSymbol::CopyAsMarkdown => "Copy as Markdown".to_string(),
+            InterfaceSymbol::CopyAsText => "Copy as text".to_string(),
+            InterfaceSymbol::ExportAsMarkdown => "Export as Markdown".to_string(),
+            InterfaceSymbol::ExportAsText => "Export as text".to_string(),
             _ => format!("{:?}", self),
         }
     }
@@ -131,7 +252,7 @@ impl Localization for InterfaceSymbol {
 
 impl Display for InterfaceSymbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.apply_locale(&*crate::LOCALE.lock().unwrap()))
+        write!(f, "{}", self.apply_locale(&crate::LOCALE.lock().unwrap()))
     }
 }
 
@@ -151,7 +272,7 @@ impl Display for LocalizedArmor<'_> {
         write!(
             f,
             "{}",
-            self.apply_locale(&*crate::LOCALE.lock().unwrap())
+            self.apply_locale(&crate::LOCALE.lock().unwrap())
         )
     }
 }
@@ -161,7 +282,7 @@ impl Display for LocalizedSkill {
         write!(
             f,
             "{}",
-            self.apply_locale(&*crate::LOCALE.lock().unwrap())
+            self.apply_locale(&crate::LOCALE.lock().unwrap())
         )
     }
 }
@@ -1,27 +1,36 @@
-mod locale;
-mod profile;
 mod style_iced;
 mod ui_iced;
-mod update;
-mod file;
 
-use std::sync::Mutex;
+use std::{env, process};
 
 use iced::{Settings, pure::Application};
-use locale::Locale;
-use once_cell::sync::Lazy;
+use rab::cli;
 
 const FONT: &[u8] = include_bytes!("fonts/FiraSans-Regular.ttf");
-// I use this global variable to use the locale within the implementations of the Display trait
-// If you have another solution don't hesitate to tell me.
-static LOCALE: Lazy<Mutex<Option<Locale>>> = Lazy::new(Default::default);
-
-const ARMORS_PATH: &str = "armors";
-const LOCALE_DIR_PATH: &str = "locale";
 
 pub fn main() -> iced::Result {
+    let args = match cli::parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    let flags = ui_iced::Flags {
+        paths: args.paths(),
+        lang: args.lang,
+        theme: args.theme,
+        offline: args.offline,
+    };
+
     ui_iced::MainApp::run(Settings {
         default_font: Some(FONT),
-        ..Default::default()
+        exit_on_close_request: false, // the app saves the session first
+        ..Settings::with_flags(flags)
     })
 }
@@ -0,0 +1,221 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+const APP_DIR: &str = "rab";
+const ARMORS_DIR: &str = "armors";
+const LOCALE_DIR: &str = "locale";
+const PROFILE_FILE: &str = "profile.ron";
+
+// in the armors directory: helmets, chests, arms, waists then legs
+pub const ARMOR_FILES: [&str; 5] = [
+    "helmets.ron",
+    "chests.ron",
+    "arms.ron",
+    "waists.ron",
+    "legs.ron",
+];
+
+// the user files of the versions using the working directory
+const LEGACY_DATA_FILES: [&str; 6] = [
+    "talismans.ron",
+    "builds.ron",
+    "session.ron",
+    "blacklist.ron",
+    "jewels.ron",
+    "presets.ron",
+];
+
+// Where RAB reads and writes its files. The profile lives in the config
+// directory, everything else (armors, locales, talismans...) in the data directory.
+#[derive(Debug, Clone, Default)]
+pub struct Paths {
+    pub data_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub armors_dir: PathBuf,
+    pub locale_dir: PathBuf,
+}
+
+impl Paths {
+    // The portable mode is used if RAB files sit next to the executable,
+    // otherwise the XDG directories (or %APPDATA% on Windows).
+    pub fn resolve() -> Self {
+        if let Some(exe_dir) = executable_dir() {
+            if is_portable(&exe_dir) {
+                println!("Portable mode, files in {}", exe_dir.display());
+                return Self::in_dirs(exe_dir.clone(), exe_dir);
+            }
+        }
+
+        match (user_data_dir(), user_config_dir()) {
+            (Some(data_dir), Some(config_dir)) => {
+                let paths = Self::in_dirs(data_dir.join(APP_DIR), config_dir.join(APP_DIR));
+                paths.migrate_from_working_dir();
+                paths.create_dirs();
+                paths
+            }
+            // no home, we can only use the working directory like before
+            _ => Self::in_dirs(PathBuf::from("."), PathBuf::from(".")),
+        }
+    }
+
+    // the armors and the locales are in the data directory
+    pub fn in_dirs(data_dir: PathBuf, config_dir: PathBuf) -> Self {
+        Self {
+            armors_dir: data_dir.join(ARMORS_DIR),
+            locale_dir: data_dir.join(LOCALE_DIR),
+            data_dir,
+            config_dir,
+        }
+    }
+
+    pub fn armor_file(&self, file: &str) -> PathBuf {
+        self.armors_dir.join(file)
+    }
+
+    pub fn data_file(&self, file: &str) -> PathBuf {
+        self.data_dir.join(file)
+    }
+
+    pub fn profile_file(&self) -> PathBuf {
+        self.config_dir.join(PROFILE_FILE)
+    }
+
+    pub fn create_dirs(&self) {
+        for dir in [
+            self.data_dir.clone(),
+            self.config_dir.clone(),
+            self.armors_dir.clone(),
+            self.locale_dir.clone(),
+        ] {
+            if let Err(err) = fs::create_dir_all(&dir) {
+                println!("Can't create directory {}:\n{}", dir.display(), err)
+            }
+        }
+    }
+
+    // First run with the new directories: the files of the older versions
+    // are copied from the working directory. They are not removed in case
+    // of a downgrade.
+    fn migrate_from_working_dir(&self) {
+        if self.data_dir.exists() || self.config_dir.exists() {
+            return;
+        }
+        let working_dir = Path::new(".");
+        if !is_portable(working_dir) {
+            return;
+        }
+
+        println!(
+            "First run, copying the files of the working directory to {} and {}",
+            self.data_dir.display(),
+            self.config_dir.display()
+        );
+        self.create_dirs();
+
+        let mut copies: Vec<(PathBuf, PathBuf)> = LEGACY_DATA_FILES
+            .iter()
+            .map(|file| (working_dir.join(file), self.data_file(file)))
+            .collect();
+        copies.push((working_dir.join(PROFILE_FILE), self.profile_file()));
+        for (dir, new_dir) in [
+            (working_dir.join(ARMORS_DIR), self.armors_dir.clone()),
+            (working_dir.join(LOCALE_DIR), self.locale_dir.clone()),
+        ] {
+            if let Ok(entries) = fs::read_dir(dir) {
+                for entry in entries.flatten() {
+                    copies.push((entry.path(), new_dir.join(entry.file_name())));
+                }
+            }
+        }
+
+        for (from, to) in copies.into_iter().filter(|(from, _)| from.is_file()) {
+            if let Err(err) = fs::copy(&from, &to) {
+                println!("Can't copy {}:\n{}", from.display(), err)
+            }
+        }
+    }
+}
+
+// the armors or the profile are there since the first launch
+fn is_portable(dir: &Path) -> bool {
+    dir.join(ARMORS_DIR).is_dir() || dir.join(PROFILE_FILE).is_file()
+}
+
+fn executable_dir() -> Option<PathBuf> {
+    env::current_exe()
+        .ok()?
+        .parent()
+        .map(|dir| dir.to_path_buf())
+}
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    // the XDG spec says to ignore relative paths
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+#[cfg(windows)]
+fn user_data_dir() -> Option<PathBuf> {
+    env_dir("APPDATA")
+}
+
+#[cfg(not(windows))]
+fn user_data_dir() -> Option<PathBuf> {
+    env_dir("XDG_DATA_HOME").or_else(|| Some(env_dir("HOME")?.join(".local/share")))
+}
+
+#[cfg(windows)]
+fn user_config_dir() -> Option<PathBuf> {
+    env_dir("APPDATA")
+}
+
+#[cfg(not(windows))]
+fn user_config_dir() -> Option<PathBuf> {
+    env_dir("XDG_CONFIG_HOME").or_else(|| Some(env_dir("HOME")?.join(".config")))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{
+        process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    // The tests run in parallel, and so can several test runs:
+    // each test gets its own empty directory.
+    pub(crate) fn unique_temp_dir(name: &str) -> PathBuf {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("rab_test_{}_{}_{}", name, process::id(), count));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn the_files_are_placed_in_their_directory() {
+        let paths = Paths::in_dirs(PathBuf::from("data"), PathBuf::from("config"));
+        assert_eq!(
+            paths.armor_file("helm.json"),
+            Path::new("data/armors/helm.json")
+        );
+        assert_eq!(paths.locale_dir, Path::new("data/locale"));
+        assert_eq!(paths.data_file("builds.ron"), Path::new("data/builds.ron"));
+        assert_eq!(paths.profile_file(), Path::new("config/profile.ron"));
+    }
+
+    #[test]
+    fn a_directory_with_armors_is_portable() {
+        let dir = unique_temp_dir("portable");
+        assert!(!is_portable(&dir));
+
+        Paths::in_dirs(dir.clone(), dir.clone()).create_dirs();
+        let portable = is_portable(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(portable);
+    }
+}
@@ -1,21 +1,146 @@
 use std::{
     collections::HashMap,
     fs::{canonicalize, write, File},
+    io::Read,
+    path::Path,
 };
 
 use ron::{
-    de::from_reader,
+    de::from_str,
     ser::{to_string_pretty, PrettyConfig},
-    Error,
+    Error, Value,
 };
+use serde::{Deserialize, Serialize};
 
-// this profile will be useful for saving user
-// preferences after closing RAB, like the language
-pub fn get_profile(path: &str) -> Result<HashMap<String, String>, Error> {
-    from_reader(File::open(path)?)
+use crate::build_stats::BuildSort;
+
+// Must be increased when a setting is renamed or changes meaning,
+// the older profiles are then migrated in get_profile.
+pub const PROFILE_VERSION: u32 = 1;
+
+const KNOWN_KEYS: [&str; 6] = [
+    "version",
+    "lang",
+    "theme",
+    "sort",
+    "ignore_jewel_inventory",
+    "restore_results",
+];
+
+#[derive(Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+// User preferences kept after closing RAB
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Profile {
+    pub version: u32,
+    pub lang: String,
+    pub theme: Theme,
+    pub sort: BuildSort,
+    pub ignore_jewel_inventory: bool, // without inventory the jewels are unlimited
+    pub restore_results: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            version: PROFILE_VERSION,
+            lang: "English".to_string(),
+            theme: Theme::Dark,
+            sort: BuildSort::default(),
+            ignore_jewel_inventory: true,
+            restore_results: false,
+        }
+    }
+}
+
+// The unknown keys are printed then ignored.
+pub fn get_profile(path: &Path) -> Result<Profile, Error> {
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+
+    for key in unknown_keys(&text) {
+        println!("Unknown profile setting ignored: {}", key);
+    }
+
+    // before the versions, the profile was a map of strings
+    if let Ok(map) = from_str::<HashMap<String, String>>(&text) {
+        println!(
+            "Old profile format, migrating to version {}.",
+            PROFILE_VERSION
+        );
+        return Ok(migrate_string_map(map));
+    }
+
+    let profile: Profile = from_str(&text)?;
+    if profile.version > PROFILE_VERSION {
+        println!(
+            "The profile comes from a newer version of RAB (version {}), some settings may be lost.",
+            profile.version
+        );
+    }
+    Ok(Profile {
+        version: PROFILE_VERSION,
+        ..profile
+    })
+}
+
+// Works with both formats, the struct is read as a map too.
+// A Value can't be turned into a Profile (the unit variants of the
+// enums are lost), it is only used to find the keys.
+fn unknown_keys(text: &str) -> Vec<String> {
+    let map = match from_str(text) {
+        Ok(Value::Map(map)) => map,
+        _ => return Vec::new(),
+    };
+    map.keys()
+        .filter_map(|key| match key {
+            Value::String(key) if !KNOWN_KEYS.contains(&key.as_str()) => Some(key.clone()),
+            _ => None,
+        })
+        .collect()
 }
 
-pub fn save_profile(profile: &HashMap<String, String>, path: &str) -> Result<String, Error> {
+fn migrate_string_map(map: HashMap<String, String>) -> Profile {
+    let mut profile = Profile::default();
+    let invalid = |key: &str, value: &str| {
+        println!("Invalid value for {} in the profile: {}", key, value);
+    };
+
+    for (key, value) in map.iter() {
+        match key.as_str() {
+            "lang" => profile.lang = value.clone(),
+            "theme" => match value.as_str() {
+                "dark" => profile.theme = Theme::Dark,
+                "light" => profile.theme = Theme::Light,
+                _ => invalid(key, value),
+            },
+            "sort" => match BuildSort::from_key(value) {
+                Some(sort) => profile.sort = sort,
+                None => invalid(key, value),
+            },
+            "ignore_jewel_inventory" => match value.parse() {
+                Ok(ignore) => profile.ignore_jewel_inventory = ignore,
+                Err(_) => invalid(key, value),
+            },
+            "restore_results" => match value.parse() {
+                Ok(restore) => profile.restore_results = restore,
+                Err(_) => invalid(key, value),
+            },
+            // already reported
+            _ => (),
+        }
+    }
+
+    profile
+}
+
+pub fn save_profile(profile: &Profile, path: &Path) -> Result<String, Error> {
     let text = to_string_pretty(profile, PrettyConfig::new().indentor("  ".to_string()))?;
 
     write(path, text)?;
@@ -24,3 +149,70 @@ pub fn save_profile(profile: &HashMap<String, String>, path: &str) -> Result<Str
 
     Ok(path.to_string_lossy().into_owned())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paths::tests::unique_temp_dir;
+
+    #[test]
+    fn saved_profile_is_read_back() {
+        let dir = unique_temp_dir("profile");
+        let path = dir.join("profile.ron");
+        let profile = Profile {
+            lang: "Français".to_string(),
+            theme: Theme::Light,
+            sort: BuildSort::FreeSlots,
+            ignore_jewel_inventory: false,
+            restore_results: true,
+            ..Default::default()
+        };
+        save_profile(&profile, &path).unwrap();
+        let read = get_profile(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.version, PROFILE_VERSION);
+        assert_eq!(read.lang, profile.lang);
+        assert!(read.theme == Theme::Light);
+        assert_eq!(read.sort, BuildSort::FreeSlots);
+        assert!(!read.ignore_jewel_inventory);
+        assert!(read.restore_results);
+    }
+
+    #[test]
+    fn old_profile_is_migrated() {
+        let dir = unique_temp_dir("old_profile");
+        let path = dir.join("profile.ron");
+        write(
+            &path,
+            r#"{"lang": "Français", "theme": "light", "sort": "extra_skills", "unknown": "1"}"#,
+        )
+        .unwrap();
+        let read = get_profile(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.version, PROFILE_VERSION);
+        assert_eq!(read.lang, "Français");
+        assert!(read.theme == Theme::Light);
+        assert_eq!(read.sort, BuildSort::ExtraSkills);
+        assert!(read.ignore_jewel_inventory);
+    }
+
+    #[test]
+    fn unknown_keys_are_found_in_both_formats() {
+        let dir = unique_temp_dir("unknown_keys");
+        let path = dir.join("profile.ron");
+        let text = r#"(version: 1, lang: "Français", theme: Light, colour: "red")"#;
+        write(&path, text).unwrap();
+        let read = get_profile(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.lang, "Français");
+        assert!(read.theme == Theme::Light);
+        assert_eq!(unknown_keys(text), ["colour"]);
+        assert_eq!(
+            unknown_keys(r#"{"lang": "Français", "unknown": "1"}"#),
+            ["unknown"]
+        );
+    }
+}
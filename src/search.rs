@@ -0,0 +1,924 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    iter,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use iced_futures::futures::channel::oneshot;
+use rab_core::{
+    armor_and_skills::{Armor, Gender, Skill},
+    build_search::{pre_selection_then_brute_force_search, AllArmorSlices, Build, Jewels},
+};
+
+use crate::{
+    blacklist::Blacklist,
+    build_stats::{
+        build_jewel_counts, build_key, build_parts, free_slots, has_free_slots, missing_jewels,
+        total_defense, total_resistances,
+    },
+    file::AllArmors,
+};
+
+// The brute force search can take a long time with a lot of wishes.
+// To keep the window responsive, the helmets are split in chunks and
+// each chunk is searched in another thread. Between two chunks we can
+// update the progress bar or stop the search.
+const MAX_SEARCH_STEPS: usize = 50;
+
+#[derive(Clone)]
+pub struct SearchQuery {
+    pub wishes: Vec<(Skill, u8)>,
+    // skill, level, weight
+    pub optional_wishes: Vec<(Skill, u8, u8)>,
+    // skill, highest allowed level
+    pub avoided_skills: Vec<(Skill, u8)>,
+    // slots left without jewel, by level (index 0 = level 1)
+    pub free_slot_requirements: [u8; 3],
+    pub gender: Gender,
+    pub weapon_slots: [u8; 3],
+    pub stat_requirements: StatRequirements,
+    pub locks: [Option<Armor>; 6], // same order as build_parts
+    // None if the jewels are unlimited
+    pub jewel_inventory: Option<HashMap<Skill, u8>>,
+}
+
+impl SearchQuery {
+    // 0 if the skill is not wished
+    pub fn wished_level(&self, skill: Skill) -> u8 {
+        self.wishes
+            .iter()
+            .find(|(wish, _)| *wish == skill)
+            .map_or(0, |(_, level)| *level)
+    }
+
+    pub fn is_avoided(&self, skill: Skill) -> bool {
+        self.avoided_skills
+            .iter()
+            .any(|(avoided, _)| *avoided == skill)
+    }
+
+    // Checks the requirements that the engine doesn't know. The engine keeps the
+    // builds with the fewest pieces, so a build that would only meet them with
+    // another piece or other jewels is not found.
+    fn accepts(&self, build: &Build) -> bool {
+        self.stat_requirements.is_satisfied_by(build)
+            && has_free_slots(
+                free_slots(build, &self.weapon_slots),
+                self.free_slot_requirements,
+            )
+            && (self.avoided_skills.is_empty() || {
+                let skills = build.get_all_skills_and_amounts();
+                self.avoided_skills.iter().all(|(skill, max_level)| {
+                    skills.get(skill).is_none_or(|level| level <= max_level)
+                })
+            })
+            && self.jewel_inventory.as_ref().is_none_or(|inventory| {
+                missing_jewels(&build_jewel_counts(build), inventory).is_empty()
+            })
+    }
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct StatRequirements {
+    pub defense: Option<u16>,
+    pub resistances: [Option<i16>; 5], // fire, water, thunder, ice, dragon
+}
+
+impl StatRequirements {
+    // same order as armor_stats
+    fn minimums(&self) -> [Option<i32>; 6] {
+        let [fire, water, thunder, ice, dragon] = self.resistances.map(|r| r.map(i32::from));
+        [
+            self.defense.map(i32::from),
+            fire,
+            water,
+            thunder,
+            ice,
+            dragon,
+        ]
+    }
+
+    fn is_satisfied_by(&self, build: &Build) -> bool {
+        self.defense
+            .is_none_or(|defense| total_defense(build) >= defense)
+            && total_resistances(build)
+                .iter()
+                .zip(self.resistances)
+                .all(|(value, minimum)| minimum.is_none_or(|minimum| *value >= minimum))
+    }
+}
+
+fn armor_stats(armor: &Armor) -> [i32; 6] {
+    [
+        i32::from(armor.defense),
+        i32::from(armor.fire),
+        i32::from(armor.water),
+        i32::from(armor.thunder),
+        i32::from(armor.ice),
+        i32::from(armor.dragon),
+    ]
+}
+
+#[derive(Default, Clone)]
+pub struct ArmorLists {
+    pub helmets: Vec<Armor>,
+    pub chests: Vec<Armor>,
+    pub arms: Vec<Armor>,
+    pub waists: Vec<Armor>,
+    pub legs: Vec<Armor>,
+    pub talismans: Vec<Armor>,
+}
+
+impl ArmorLists {
+    // the blacklisted pieces are not given to the search engine
+    pub fn new(armors: AllArmors, talismans: Vec<Armor>, blacklist: &Blacklist) -> Self {
+        let (helmets, chests, arms, waists, legs) = armors;
+        let allowed = |list: Vec<Armor>| -> Vec<Armor> {
+            list.into_iter()
+                .filter(|armor| !blacklist.contains(armor))
+                .collect()
+        };
+        Self {
+            helmets: allowed(helmets),
+            chests: allowed(chests),
+            arms: allowed(arms),
+            waists: allowed(waists),
+            legs: allowed(legs),
+            talismans,
+        }
+    }
+
+    fn as_slices(&self) -> AllArmorSlices<'_> {
+        AllArmorSlices {
+            helmets: &self.helmets,
+            chests: &self.chests,
+            arms: &self.arms,
+            waists: &self.waists,
+            legs: &self.legs,
+            talismans: &self.talismans,
+        }
+    }
+
+    fn lists_mut(&mut self) -> [&mut Vec<Armor>; 6] {
+        [
+            &mut self.helmets,
+            &mut self.chests,
+            &mut self.arms,
+            &mut self.waists,
+            &mut self.legs,
+            &mut self.talismans,
+        ]
+    }
+
+    // Removes the pieces that already have too much of an avoided skill.
+    // The engine only adds jewels of wished skills, so the other pieces can stay.
+    fn prune_by_avoided_skills(&mut self, avoided_skills: &[(Skill, u8)]) {
+        for list in self.lists_mut() {
+            list.retain(|armor| {
+                armor.skills.iter().all(|(skill, level)| {
+                    avoided_skills
+                        .iter()
+                        .all(|(avoided, max_level)| avoided != skill || level <= max_level)
+                })
+            });
+        }
+    }
+
+    // Removes the pieces that can't reach the minimums, even
+    // with the best pieces of the other slots.
+    fn prune_by_stats(&mut self, requirements: &StatRequirements) {
+        let minimums = requirements.minimums();
+        if minimums.iter().all(Option::is_none) {
+            return;
+        }
+
+        let mut lists = self.lists_mut();
+
+        // a free slot brings nothing, so the best value is at least 0
+        let best_by_slot: Vec<[i32; 6]> = lists
+            .iter()
+            .map(|list| {
+                let mut best = [0; 6];
+                for armor in list.iter() {
+                    for (best, value) in best.iter_mut().zip(armor_stats(armor)) {
+                        *best = (*best).max(value);
+                    }
+                }
+                best
+            })
+            .collect();
+
+        let mut best_total = [0; 6];
+        for best in best_by_slot.iter() {
+            for (total, value) in best_total.iter_mut().zip(best) {
+                *total += value;
+            }
+        }
+
+        for (list, best) in lists.iter_mut().zip(best_by_slot) {
+            list.retain(|armor| {
+                armor_stats(armor)
+                    .iter()
+                    .zip(best)
+                    .zip(best_total)
+                    .zip(minimums)
+                    .all(|(((value, best), total), minimum)| {
+                        minimum.is_none_or(|minimum| total - best + value >= minimum)
+                    })
+            });
+        }
+    }
+}
+
+pub struct SearchJob {
+    query: SearchQuery,
+    armors: ArmorLists,
+    // the wishes given to the engine, see wish_variants
+    variants: Vec<Vec<(Skill, u8)>>,
+    // the helmets that the engine keeps for each variant, see best_candidates
+    helmets: Vec<Vec<Armor>>,
+    // steps of one variant of the wishes
+    chunk_count: usize,
+    cancelled: AtomicBool,
+}
+
+// Up to this number of optional wishes, every combination of them is searched.
+const MAX_OPTIONAL_COMBINATIONS: usize = 4;
+
+// The engine only knows required wishes. The builds with optional wishes are
+// found by searching again with the optional wishes added: every combination
+// of them (the biggest first) then none. With more than
+// MAX_OPTIONAL_COMBINATIONS optional wishes there would be too many searches,
+// only all of them, each one alone and none are searched. A build that only
+// fits some of them (but not one alone) is then missed.
+fn wish_variants(query: &SearchQuery) -> Vec<Vec<(Skill, u8)>> {
+    let optional = &query.optional_wishes;
+    let mut subsets: Vec<Vec<usize>> = if optional.len() <= MAX_OPTIONAL_COMBINATIONS {
+        (1..1 << optional.len())
+            .map(|mask: u32| {
+                (0..optional.len())
+                    .filter(|index| mask & (1 << index) != 0)
+                    .collect()
+            })
+            .collect()
+    } else {
+        iter::once((0..optional.len()).collect())
+            .chain((0..optional.len()).map(|index| vec![index]))
+            .collect()
+    };
+    subsets.sort_by_key(|subset| Reverse(subset.len()));
+
+    let mut variants: Vec<Vec<(Skill, u8)>> = subsets
+        .into_iter()
+        .map(|subset| {
+            query
+                .wishes
+                .iter()
+                .copied()
+                .chain(subset.into_iter().map(|index| {
+                    let (skill, level, _) = optional[index];
+                    (skill, level)
+                }))
+                .collect()
+        })
+        .collect();
+    // without any wish the engine would give every combination of pieces
+    if optional.is_empty() || !query.wishes.is_empty() {
+        variants.push(query.wishes.clone());
+    }
+    variants
+}
+
+impl SearchJob {
+    pub fn new(query: SearchQuery, mut armors: ArmorLists) -> Self {
+        // a locked slot can only receive its piece
+        for (list, lock) in armors.lists_mut().into_iter().zip(&query.locks) {
+            if let Some(armor) = lock {
+                *list = vec![armor.clone()];
+            }
+        }
+        armors.prune_by_avoided_skills(&query.avoided_skills);
+        armors.prune_by_stats(&query.stat_requirements);
+        let variants = wish_variants(&query);
+        let helmets: Vec<Vec<Armor>> = variants
+            .iter()
+            .map(|wishes| best_candidates(wishes, &armors.helmets, query.gender))
+            .collect();
+        let chunk_count = helmets
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(1)
+            .clamp(1, MAX_SEARCH_STEPS);
+        Self {
+            query,
+            armors,
+            variants,
+            helmets,
+            chunk_count,
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn query(&self) -> &SearchQuery {
+        &self.query
+    }
+
+    // the lists given to the engine, after the locks and the pruning
+    pub fn armors(&self) -> &ArmorLists {
+        &self.armors
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.chunk_count * self.variants.len()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // None if the search has been cancelled
+    pub fn run_step(&self, step: usize) -> Option<Vec<Build>> {
+        if self.is_cancelled() {
+            return None;
+        }
+
+        let variant = step / self.chunk_count;
+        let chunk = step % self.chunk_count;
+        let helmets = &self.helmets[variant];
+        let start = chunk * helmets.len() / self.chunk_count;
+        let end = (chunk + 1) * helmets.len() / self.chunk_count;
+        if chunk > 0 && start == end {
+            return Some(Vec::new());
+        }
+
+        let mut builds = self.search(&self.variants[variant], &helmets[start..end]);
+        // builds without helmet are found by every chunk, we keep those of the first one
+        if chunk > 0 {
+            builds.retain(|build| build.helmet.is_some());
+        }
+        Some(builds)
+    }
+
+    // Every step in the current thread, for the command line.
+    // The builds found by several variants of the wishes are kept once.
+    pub fn run_all(&self) -> Vec<Build> {
+        let mut found = HashSet::new();
+        let mut builds = Vec::new();
+        for step in 0..self.step_count() {
+            // only None if cancelled
+            let step_builds = match self.run_step(step) {
+                Some(step_builds) => step_builds,
+                None => break,
+            };
+            builds.extend(
+                step_builds
+                    .into_iter()
+                    .filter(|build| found.insert(build_key(build))),
+            );
+        }
+        builds
+    }
+
+    // search with every armor at once, with other wishes than the query's
+    pub fn has_builds(&self, wishes: &[(Skill, u8)]) -> bool {
+        !self.search(wishes, &self.armors.helmets).is_empty()
+    }
+
+    // The builds of the engine that meet the whole query. A build made of a
+    // part of the pieces of another one has the same helmet or none, so it is
+    // found by the same search and the chunks give the builds of a single search.
+    fn search(&self, wishes: &[(Skill, u8)], helmets: &[Armor]) -> Vec<Build> {
+        let mut builds: Vec<Build> = pre_selection_then_brute_force_search(
+            wishes,
+            AllArmorSlices {
+                helmets,
+                ..self.armors.as_slices()
+            },
+            self.query.gender,
+            self.query.weapon_slots,
+        )
+        .into_iter()
+        .map(|mut build| {
+            self.add_locked_pieces(&mut build);
+            build
+        })
+        .filter(|build| self.query.accepts(build))
+        .collect();
+        // the engine drops a locked piece without wished skill or keeps
+        // the build without it, adding it can make a build bigger than another
+        keep_minimal_builds(&mut builds);
+        builds
+    }
+
+    fn add_locked_pieces(&self, build: &mut Build) {
+        let parts = [
+            &mut build.helmet,
+            &mut build.chest,
+            &mut build.arm,
+            &mut build.waist,
+            &mut build.leg,
+            &mut build.talisman,
+        ];
+        for (part, lock) in parts.into_iter().zip(&self.query.locks) {
+            if let Some(armor) = lock {
+                part.get_or_insert_with(|| (armor.clone(), Jewels::default()));
+            }
+        }
+    }
+
+    // Highest level of the skill that still gives builds when added to the wishes.
+    // The level `reachable` must be known to give builds (0 = no skill).
+    // None if the job has been cancelled.
+    pub fn max_level(&self, wishes: &[(Skill, u8)], skill: Skill, reachable: u8) -> Option<u8> {
+        let mut wishes = wishes.to_vec();
+        let index = match wishes.iter().position(|(wish, _)| *wish == skill) {
+            Some(index) => index,
+            None => {
+                wishes.push((skill, 0));
+                wishes.len() - 1
+            }
+        };
+
+        // the levels can be searched by dichotomy, if a level is reachable
+        // then the lower ones are reachable too
+        let mut low = reachable;
+        let mut high = skill.get_limit();
+        while low < high {
+            if self.is_cancelled() {
+                return None;
+            }
+            let middle = (low + high).div_ceil(2);
+            wishes[index].1 = middle;
+            if self.has_builds(&wishes) {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+        Some(low)
+    }
+
+    // Highest level of a wish with the other wishes unchanged.
+    // None if there is no such wish too.
+    fn max_wish_level(&self, index: usize) -> Option<u8> {
+        if index >= self.query.wishes.len() {
+            return None;
+        }
+        let mut wishes = self.query.wishes.clone();
+        let (skill, _) = wishes.remove(index);
+        self.max_level(&wishes, skill, 0)
+    }
+
+    // Highest level of the skill on top of the query's wishes
+    fn max_additional_level(&self, skill: Skill) -> Option<u8> {
+        let wished_level = self.query.wished_level(skill);
+        self.max_level(&self.query.wishes, skill, wished_level)
+    }
+}
+
+// Removes the builds that have all the pieces of another build and more
+fn keep_minimal_builds(builds: &mut Vec<Build>) {
+    let pieces: Vec<Vec<Option<String>>> = builds
+        .iter()
+        .map(|build| {
+            build_parts(build)
+                .iter()
+                .map(|part| part.as_ref().map(|(armor, _)| armor.name.clone()))
+                .collect()
+        })
+        .collect();
+    let is_part = |part: &[Option<String>], whole: &[Option<String>]| {
+        part != whole
+            && part
+                .iter()
+                .zip(whole)
+                .all(|(piece, other)| piece.is_none() || piece == other)
+    };
+    let mut keep = pieces
+        .iter()
+        .map(|whole| !pieces.iter().any(|part| is_part(part, whole)))
+        .collect::<Vec<bool>>()
+        .into_iter();
+    builds.retain(|_| keep.next().unwrap_or(true));
+}
+
+// The engine only searches the pieces that no other piece of their slot beats,
+// but it doesn't export this selection. It is done here the same way on all the
+// helmets, so that a helmet beaten by one of another chunk isn't searched.
+fn best_candidates(wishes: &[(Skill, u8)], armors: &[Armor], gender: Gender) -> Vec<Armor> {
+    let armors: Vec<&Armor> = armors
+        .iter()
+        .filter(|armor| {
+            armor
+                .gender
+                .is_none_or(|armor_gender| armor_gender == gender)
+        })
+        .filter(|armor| {
+            wishes.iter().any(|(skill, _)| {
+                skill
+                    .get_jewel_size()
+                    .is_some_and(|size| armor.slots.iter().any(|slot| *slot >= size))
+                    || armor
+                        .skills
+                        .iter()
+                        .any(|(armor_skill, _)| armor_skill == skill)
+            })
+        })
+        .collect();
+    armors
+        .iter()
+        .filter(|armor| !armors.iter().any(|other| is_beaten(wishes, armor, other)))
+        .map(|armor| (*armor).clone())
+        .collect()
+}
+
+// True if `other` can give the skills that `armor` has more with jewels and
+// still has better slots. A skill without jewel can't be replaced.
+fn is_beaten(wishes: &[(Skill, u8)], armor: &Armor, other: &Armor) -> bool {
+    let mut virtual_slots = Vec::new();
+    for (wish, _) in wishes {
+        for (skill, level) in armor.skills.iter().filter(|(skill, _)| skill == wish) {
+            let other_level = other
+                .skills
+                .iter()
+                .find(|(other_skill, _)| other_skill == skill)
+                .map_or(0, |(_, level)| *level);
+            let more = level - level.min(&other_level);
+            match skill.get_jewel_size() {
+                Some(size) => virtual_slots.extend(iter::repeat_n(size, more.into())),
+                None if more > 0 => return false,
+                None => {}
+            }
+        }
+    }
+    if armor.slots.len() + virtual_slots.len() > other.slots.len() {
+        return false;
+    }
+
+    let mut slots = [armor.slots.as_slice(), &virtual_slots].concat();
+    let mut other_slots = other.slots.clone();
+    slots.sort_unstable();
+    other_slots.sort_unstable();
+    slots == other_slots && armor.slots.len() < other.slots.len()
+        || has_smaller_slots(&slots, &other_slots)
+}
+
+// The slots are sorted and compared from the biggest. True if every
+// slot is at most the other one, one of them smaller.
+fn has_smaller_slots(slots: &[u8], other_slots: &[u8]) -> bool {
+    if slots.is_empty() || other_slots.is_empty() {
+        return slots.is_empty() && !other_slots.is_empty();
+    }
+    let aligned = |slots: &[u8]| {
+        let mut aligned = [0i16; 3];
+        for (index, slot) in slots.iter().enumerate() {
+            aligned[index + 3 - slots.len()] = i16::from(*slot);
+        }
+        aligned
+    };
+    let differences: Vec<i16> = aligned(slots)
+        .iter()
+        .zip(aligned(other_slots))
+        .map(|(slot, other)| slot - other)
+        .filter(|difference| *difference != 0)
+        .collect();
+    !differences.is_empty() && differences.iter().all(|difference| *difference < 0)
+}
+
+pub async fn run_search_step(job: Arc<SearchJob>, step: usize) -> Option<Vec<Build>> {
+    run_in_background(move || job.run_step(step))
+        .await
+        .flatten()
+}
+
+pub async fn find_max_wish_level(job: Arc<SearchJob>, index: usize) -> Option<u8> {
+    run_in_background(move || job.max_wish_level(index))
+        .await
+        .flatten()
+}
+
+pub async fn find_max_additional_level(job: Arc<SearchJob>, skill: Skill) -> Option<u8> {
+    run_in_background(move || job.max_additional_level(skill))
+        .await
+        .flatten()
+}
+
+// None if the thread panicked
+pub async fn run_in_background<T: Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        // the receiver may be dropped if the application is closing
+        let _ = sender.send(task());
+    });
+    receiver.await.ok()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::build_stats::{build_parts, ArmorSlot};
+
+    pub fn armor(name: &str, skills: &[(Skill, u8)], slots: &[u8]) -> Armor {
+        Armor {
+            name: name.to_string(),
+            skills: skills.to_vec(),
+            slots: slots.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    pub fn query(wishes: &[(Skill, u8)]) -> SearchQuery {
+        SearchQuery {
+            wishes: wishes.to_vec(),
+            optional_wishes: vec![],
+            avoided_skills: vec![],
+            free_slot_requirements: [0; 3],
+            gender: Gender::Male,
+            weapon_slots: [0; 3],
+            stat_requirements: Default::default(),
+            locks: Default::default(),
+            jewel_inventory: None,
+        }
+    }
+
+    pub fn lists(helmets: Vec<Armor>, chests: Vec<Armor>, arms: Vec<Armor>) -> ArmorLists {
+        ArmorLists {
+            helmets,
+            chests,
+            arms,
+            ..Default::default()
+        }
+    }
+
+    // the names of the pieces of each build, sorted to compare the results
+    pub fn piece_names(builds: &[Build]) -> Vec<Vec<Option<String>>> {
+        let mut names: Vec<Vec<Option<String>>> = builds
+            .iter()
+            .map(|build| {
+                build_parts(build)
+                    .iter()
+                    .map(|part| part.as_ref().map(|(armor, _)| armor.name.clone()))
+                    .collect()
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn chunks_give_the_same_builds_as_one_search() {
+        // h2 beats h1 and h3, but they are in other chunks
+        let armors = lists(
+            vec![
+                armor("h1", &[(Skill::AttackBoost, 1)], &[]),
+                armor("h2", &[], &[2]),
+                armor("h3", &[(Skill::CriticalEye, 1)], &[]),
+                armor("h4", &[(Skill::AttackBoost, 2)], &[]),
+            ],
+            vec![
+                armor("c1", &[(Skill::AttackBoost, 1)], &[]),
+                armor("c2", &[(Skill::CriticalEye, 1)], &[1]),
+            ],
+            vec![armor("a", &[(Skill::CriticalEye, 1)], &[2])],
+        );
+        let wishes = [(Skill::AttackBoost, 2), (Skill::CriticalEye, 1)];
+
+        let job = SearchJob::new(query(&wishes), armors.clone());
+        assert!(job.step_count() > 1);
+        let chunked_builds = piece_names(&job.run_all());
+        let single_builds = piece_names(&pre_selection_then_brute_force_search(
+            &wishes,
+            armors.as_slices(),
+            Gender::Male,
+            [0; 3],
+        ));
+        assert_eq!(chunked_builds, single_builds);
+        assert!(!chunked_builds.is_empty());
+    }
+
+    #[test]
+    fn jewels_are_placed_on_the_smallest_slot() {
+        let armors = lists(vec![armor("h", &[], &[1, 3])], vec![], vec![]);
+        // Handicraft needs a size 3 slot, Botanist a size 1 slot
+        let job = SearchJob::new(
+            query(&[(Skill::Handicraft, 1), (Skill::Botanist, 1)]),
+            armors,
+        );
+        let builds = job.run_all();
+        assert_eq!(builds.len(), 1);
+        let (_, jewels) = builds[0].helmet.as_ref().unwrap();
+        assert!(jewels.contains(&Some(Skill::Handicraft)));
+        assert!(jewels.contains(&Some(Skill::Botanist)));
+    }
+
+    #[test]
+    fn the_minimum_defense_drops_a_build() {
+        let mut strong = armor("strong", &[(Skill::AttackBoost, 1)], &[]);
+        strong.defense = 60;
+        let mut weak = armor("weak", &[(Skill::AttackBoost, 2)], &[]);
+        weak.defense = 10;
+        let armors = lists(vec![strong, weak], vec![], vec![]);
+        let mut query = query(&[(Skill::AttackBoost, 1)]);
+
+        assert_eq!(
+            SearchJob::new(query.clone(), armors.clone())
+                .run_all()
+                .len(),
+            2
+        );
+
+        query.stat_requirements.defense = Some(50);
+        let builds = SearchJob::new(query, armors).run_all();
+        assert_eq!(builds.len(), 1);
+        assert_eq!(builds[0].helmet.as_ref().unwrap().0.name, "strong");
+    }
+
+    #[test]
+    fn the_resistance_minimum_can_drop_a_piece() {
+        let mut weak = armor("weak", &[(Skill::AttackBoost, 2)], &[]);
+        weak.fire = -3;
+        let strong = armor("strong", &[(Skill::AttackBoost, 1)], &[2]);
+        let armors = lists(vec![weak, strong], vec![], vec![]);
+        let mut query = query(&[(Skill::AttackBoost, 2)]);
+        query.stat_requirements.resistances[0] = Some(0);
+
+        let builds = SearchJob::new(query, armors).run_all();
+        assert_eq!(builds.len(), 1);
+        assert_eq!(builds[0].helmet.as_ref().unwrap().0.name, "strong");
+    }
+
+    #[test]
+    fn a_locked_piece_is_kept_without_wished_skill() {
+        let locked = armor("locked", &[(Skill::Botanist, 1)], &[]);
+        let armors = lists(
+            vec![armor("helmet", &[(Skill::AttackBoost, 1)], &[])],
+            vec![locked.clone(), armor("other", &[], &[])],
+            vec![],
+        );
+        let mut query = query(&[(Skill::AttackBoost, 1)]);
+        query.locks[ArmorSlot::Chest.index()] = Some(locked);
+
+        let builds = SearchJob::new(query, armors).run_all();
+        assert_eq!(
+            piece_names(&builds),
+            [vec![
+                Some("helmet".to_string()),
+                Some("locked".to_string()),
+                None,
+                None,
+                None,
+                None
+            ]]
+        );
+    }
+
+    #[test]
+    fn builds_need_no_more_jewels_than_owned() {
+        let armors = lists(
+            vec![
+                armor("h1", &[(Skill::AttackBoost, 1)], &[1]),
+                armor("h2", &[], &[2]),
+            ],
+            vec![armor("c", &[(Skill::AttackBoost, 1)], &[])],
+            vec![],
+        );
+        let mut query = query(&[(Skill::AttackBoost, 2)]);
+
+        // h2 needs a jewel
+        assert_eq!(
+            SearchJob::new(query.clone(), armors.clone())
+                .run_all()
+                .len(),
+            2
+        );
+
+        query.jewel_inventory = Some(HashMap::new());
+        let builds = SearchJob::new(query, armors).run_all();
+        let names: Vec<Vec<Option<String>>> = piece_names(&builds)
+            .into_iter()
+            .map(|pieces| pieces[..2].to_vec())
+            .collect();
+        assert_eq!(names, [vec![Some("h1".to_string()), Some("c".to_string())]]);
+    }
+
+    #[test]
+    fn additional_levels_use_the_free_slots_and_the_pieces() {
+        let armors = lists(
+            vec![armor("h", &[(Skill::AttackBoost, 1)], &[2, 1])],
+            vec![armor("c", &[(Skill::CriticalEye, 1)], &[])],
+            vec![],
+        );
+        let job = SearchJob::new(query(&[(Skill::AttackBoost, 1)]), armors);
+
+        // one jewel on the size 2 slot, the size 1 slot is too small for both
+        assert_eq!(job.max_additional_level(Skill::CriticalEye), Some(2));
+        assert_eq!(job.max_additional_level(Skill::AttackBoost), Some(2));
+        // no jewel and no piece with the skill
+        assert_eq!(job.max_additional_level(Skill::WindAlignment), Some(0));
+    }
+
+    #[test]
+    fn max_wish_levels_keep_the_other_wishes() {
+        let armors = lists(
+            vec![armor("h", &[(Skill::AttackBoost, 2)], &[2])],
+            vec![armor("c", &[(Skill::CriticalEye, 1)], &[2])],
+            vec![],
+        );
+        let job = SearchJob::new(
+            query(&[(Skill::AttackBoost, 1), (Skill::CriticalEye, 2)]),
+            armors,
+        );
+
+        // one slot is needed by the critical eye, none by the attack boost
+        assert_eq!(job.max_wish_level(0), Some(3));
+        assert_eq!(job.max_wish_level(1), Some(3));
+        assert_eq!(job.max_wish_level(2), None);
+
+        let job = SearchJob::new(query(&[]), lists(vec![], vec![], vec![]));
+        assert_eq!(job.max_wish_level(0), None);
+    }
+
+    #[test]
+    fn builds_with_some_of_the_optional_wishes_are_found() {
+        let armors = lists(
+            vec![
+                armor("h1", &[(Skill::CriticalEye, 1)], &[]),
+                armor("h2", &[(Skill::Guard, 1)], &[]),
+            ],
+            vec![armor("c", &[(Skill::Agitator, 1)], &[])],
+            vec![],
+        );
+        let mut query = query(&[]);
+        query.optional_wishes = vec![
+            (Skill::CriticalEye, 1, 1),
+            (Skill::Agitator, 1, 1),
+            (Skill::Guard, 1, 1),
+        ];
+
+        // h1 and c are only both needed for the first two wishes
+        let builds = SearchJob::new(query, armors).run_all();
+        assert!(piece_names(&builds)
+            .iter()
+            .any(|pieces| pieces[..2] == [Some("h1".to_string()), Some("c".to_string())]));
+    }
+
+    #[test]
+    fn avoided_skills_are_summed_over_the_pieces() {
+        let armors = lists(
+            vec![armor(
+                "h",
+                &[(Skill::AttackBoost, 1), (Skill::Guard, 1)],
+                &[],
+            )],
+            vec![
+                armor("c1", &[(Skill::AttackBoost, 1), (Skill::Guard, 1)], &[]),
+                armor("c2", &[(Skill::AttackBoost, 1)], &[]),
+            ],
+            vec![],
+        );
+        let mut query = query(&[(Skill::AttackBoost, 2)]);
+        query.avoided_skills = vec![(Skill::Guard, 1)];
+
+        // each piece has the allowed level, not both
+        let builds = SearchJob::new(query, armors).run_all();
+        let names: Vec<Vec<Option<String>>> = piece_names(&builds)
+            .into_iter()
+            .map(|pieces| pieces[..2].to_vec())
+            .collect();
+        assert_eq!(names, [vec![Some("h".to_string()), Some("c2".to_string())]]);
+    }
+
+    #[test]
+    fn the_free_slot_requirement_drops_a_build() {
+        let armors = lists(
+            vec![
+                armor("slotted", &[(Skill::AttackBoost, 1)], &[1]),
+                armor("plain", &[(Skill::AttackBoost, 2)], &[]),
+            ],
+            vec![],
+            vec![],
+        );
+        let mut query = query(&[(Skill::AttackBoost, 1)]);
+        query.free_slot_requirements = [1, 0, 0];
+
+        let builds = SearchJob::new(query.clone(), armors.clone()).run_all();
+        assert_eq!(builds.len(), 1);
+        assert_eq!(builds[0].helmet.as_ref().unwrap().0.name, "slotted");
+
+        // the free weapon slots count too
+        query.weapon_slots = [1, 0, 0];
+        assert_eq!(SearchJob::new(query, armors).run_all().len(), 2);
+    }
+}
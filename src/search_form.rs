@@ -0,0 +1,175 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{canonicalize, write, File},
+    path::Path,
+};
+
+use rab_core::armor_and_skills::{Armor, Gender, Skill};
+use ron::{
+    de::from_reader,
+    ser::{to_string_pretty, PrettyConfig},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::InterfaceSymbol,
+    search::{SearchQuery, StatRequirements},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WishKind {
+    #[default]
+    Required,
+    Optional,
+    Avoid, // the level is the highest allowed one
+}
+
+impl WishKind {
+    pub const ALL: [WishKind; 3] = [WishKind::Required, WishKind::Optional, WishKind::Avoid];
+}
+
+impl Display for WishKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WishKind::Required => InterfaceSymbol::Required,
+                WishKind::Optional => InterfaceSymbol::Optional,
+                WishKind::Avoid => InterfaceSymbol::Avoid,
+            }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct FormWish {
+    pub skill: Skill,
+    pub level: u8,
+    #[serde(default)]
+    pub kind: WishKind,
+    #[serde(default = "default_weight")]
+    pub weight: u8,
+}
+
+// the weight of an optional wish is between 1 and this
+pub const MAX_WISH_WEIGHT: u8 = 10;
+
+fn default_weight() -> u8 {
+    1
+}
+
+// Everything the user fills in the main page before a search
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SearchForm {
+    pub wishes: Vec<FormWish>,
+    pub gender: Gender,
+    pub weapon_slots: [u8; 3],
+    pub free_slots: [u8; 3], // index 0 = level 1
+    pub min_defense: Option<u16>,
+    pub min_resistances: [Option<i16>; 5], // fire, water, thunder, ice, dragon
+    pub skill_filter: String,
+}
+
+impl SearchForm {
+    // the locks and the jewels are not part of the form
+    pub fn query(
+        &self,
+        locks: [Option<Armor>; 6],
+        jewel_inventory: Option<HashMap<Skill, u8>>,
+    ) -> SearchQuery {
+        let wishes = |kind: WishKind| self.wishes.iter().filter(move |wish| wish.kind == kind);
+        SearchQuery {
+            wishes: wishes(WishKind::Required)
+                .map(|wish| (wish.skill, wish.level))
+                .collect(),
+            optional_wishes: wishes(WishKind::Optional)
+                .map(|wish| (wish.skill, wish.level, wish.weight))
+                .collect(),
+            avoided_skills: wishes(WishKind::Avoid)
+                .map(|wish| (wish.skill, wish.level))
+                .collect(),
+            gender: self.gender,
+            weapon_slots: self.weapon_slots,
+            free_slot_requirements: self.free_slots,
+            stat_requirements: StatRequirements {
+                defense: self.min_defense,
+                resistances: self.min_resistances,
+            },
+            locks,
+            jewel_inventory,
+        }
+    }
+}
+
+pub fn get_presets(path: &Path) -> Result<HashMap<String, SearchForm>, Error> {
+    from_reader(File::open(path)?)
+}
+
+pub fn save_presets(presets: &HashMap<String, SearchForm>, path: &Path) -> Result<String, Error> {
+    let text = to_string_pretty(presets, PrettyConfig::new().indentor("  ".to_string()))?;
+
+    write(path, text)?;
+
+    let path = canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// A preset name can't be blank or taken by another preset,
+// `current` is the name of the preset being saved or renamed.
+pub fn is_valid_preset_name(
+    presets: &HashMap<String, SearchForm>,
+    name: &str,
+    current: Option<&str>,
+) -> bool {
+    !name.trim().is_empty() && (current == Some(name) || !presets.contains_key(name))
+}
+
+// false if the preset doesn't exist or the new name is not valid
+pub fn rename_preset(
+    presets: &mut HashMap<String, SearchForm>,
+    old_name: &str,
+    name: &str,
+) -> bool {
+    if !is_valid_preset_name(presets, name, Some(old_name)) {
+        return false;
+    }
+    match presets.remove(old_name) {
+        Some(form) => {
+            presets.insert(name.to_string(), form);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preset_names_must_be_new_and_not_blank() {
+        let mut presets = HashMap::from([
+            ("a".to_string(), SearchForm::default()),
+            ("b".to_string(), SearchForm::default()),
+        ]);
+
+        assert!(!is_valid_preset_name(&presets, " ", None));
+        assert!(!is_valid_preset_name(&presets, "a", None));
+        // the selected preset can be saved again
+        assert!(is_valid_preset_name(&presets, "a", Some("a")));
+        assert!(is_valid_preset_name(&presets, "c", Some("a")));
+
+        assert!(!rename_preset(&mut presets, "a", "b"));
+        assert!(!rename_preset(&mut presets, "a", ""));
+        assert!(!rename_preset(&mut presets, "missing", "c"));
+        assert!(rename_preset(&mut presets, "a", "c"));
+        let mut names: Vec<&String> = presets.keys().collect();
+        names.sort();
+        assert_eq!(names, ["b", "c"]);
+    }
+}
@@ -0,0 +1,276 @@
+use std::{collections::HashMap, io::Read};
+
+use rab_core::{
+    armor_and_skills::{armor_to_talisman, talisman_to_armor, Armor, Skill, Talisman},
+    build_search::Build,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    blacklist::{get_blacklist, BLACKLIST_FILE},
+    file::{
+        get_all_armors_from_file, get_jewel_inventory, get_saved_builds, get_talismans,
+        save_builds, save_talismans_to_file, AllArmors, BUILDS_FILE, JEWELS_FILE, TALISMANS_FILE,
+    },
+    locale::{get_locales, LocalizedSkill},
+    paths::Paths,
+    profile::get_profile,
+    search::ArmorLists,
+    search_form::SearchForm,
+    Engine, LOCALE,
+};
+
+pub const DEFAULT_PORT: u16 = 8337;
+
+// far more than a search form or the talismans
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+pub const USAGE: &str = "Usage: rab-cli serve [OPTIONS]
+
+Starts an HTTP server on localhost, every body is in JSON.
+
+  GET    /skills          Skills with their localized names
+  GET    /armors          Helmets, chests, arms, waists and legs
+  POST   /search          Search with a search form, returns the sorted builds
+  GET    /builds          Saved builds by name
+  PUT    /builds/<NAME>   Saves a build
+  DELETE /builds/<NAME>   Removes a saved build
+  GET    /talismans       Talismans
+  PUT    /talismans       Replaces the talismans
+
+Options:
+  --port <PORT>        Port of the server (default 8337)
+  --data-dir <DIR>     Directory of every RAB file (profile included)
+  --armors-dir <DIR>   Directory of the armor files
+  --locale-dir <DIR>   Directory of the locale files
+  --lang <NAME>        Language of the skill names, like English
+  -h, --help           Print this help";
+
+// An error sent to the client with its HTTP status
+struct ApiError(u16, String);
+
+type ApiResult = Result<String, ApiError>;
+
+// The armors are read once, the other files at each request
+// since the interface can change them while the server runs.
+struct ServerState {
+    paths: Paths,
+    armors: AllArmors,
+}
+
+// Only one request at a time, a long search delays the next requests.
+pub fn serve(paths: Paths, port: u16, lang: Option<String>) -> Result<(), String> {
+    let locales = get_locales(&paths.locale_dir).unwrap_or_default();
+    let lang = lang.unwrap_or_else(|| get_profile(&paths.profile_file()).unwrap_or_default().lang);
+    *LOCALE.lock().unwrap() = locales.get(&lang).cloned();
+
+    let armors = get_all_armors_from_file(&paths)
+        .map_err(|err| format!("Can't read the armor files:\n{}", err))?;
+    let state = ServerState { paths, armors };
+
+    let address = format!("127.0.0.1:{}", port);
+    let server = Server::http(&address).map_err(|err| err.to_string())?;
+    println!("Listening on http://{}", address);
+
+    for mut request in server.incoming_requests() {
+        let (status, body) = match state.handle(&mut request) {
+            Ok(body) => (200, body),
+            Err(ApiError(status, message)) => (status, json!({ "error": message }).to_string()),
+        };
+        println!("{} {} {}", request.method(), request.url(), status);
+
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap(),
+            );
+        if let Err(err) = request.respond(response) {
+            println!("Can't send the response:\n{}", err)
+        }
+    }
+
+    Ok(())
+}
+
+impl ServerState {
+    fn handle(&self, request: &mut Request) -> ApiResult {
+        let method = request.method().clone();
+        let url = request.url().to_string();
+        let path = url.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        match (&method, segments.as_slice()) {
+            (Method::Get, ["skills"]) => self.skills(),
+            (Method::Get, ["armors"]) => self.armors(),
+            (Method::Post, ["search"]) => self.search(read_json(request)?),
+            (Method::Get, ["builds"]) => to_json(&self.saved_builds()?),
+            (Method::Put, ["builds", name]) => {
+                let name = percent_decode(name)?;
+                self.put_build(name, read_json(request)?)
+            }
+            (Method::Delete, ["builds", name]) => self.delete_build(&percent_decode(name)?),
+            (Method::Get, ["talismans"]) => self.talismans(),
+            (Method::Put, ["talismans"]) => self.put_talismans(read_json(request)?),
+            _ => Err(ApiError(
+                404,
+                format!("Unknown endpoint: {} {}", method, path),
+            )),
+        }
+    }
+
+    fn skills(&self) -> ApiResult {
+        let skills: Vec<_> = Skill::ALL
+            .iter()
+            .map(|skill| json!({ "skill": skill, "name": LocalizedSkill(*skill).to_string() }))
+            .collect();
+        to_json(&skills)
+    }
+
+    fn armors(&self) -> ApiResult {
+        let (helmets, chests, arms, waists, legs) = &self.armors;
+        to_json(&json!({
+            "helmets": helmets,
+            "chests": chests,
+            "arms": arms,
+            "waists": waists,
+            "legs": legs,
+        }))
+    }
+
+    // Same settings as the interface: blacklist, jewel inventory and sort of the profile.
+    fn search(&self, form: SearchForm) -> ApiResult {
+        // like rab-cli, an empty form would list every combination
+        if form.wishes.is_empty() {
+            return Err(ApiError(400, "No wish given".to_string()));
+        }
+        let profile = get_profile(&self.paths.profile_file()).unwrap_or_default();
+        let jewel_inventory = if profile.ignore_jewel_inventory {
+            None
+        } else {
+            Some(get_jewel_inventory(&self.paths.data_file(JEWELS_FILE)).unwrap_or_default())
+        };
+
+        let mut engine = Engine::new(self.armor_lists());
+        engine.set_sort(profile.sort);
+        engine.set_jewel_inventory(jewel_inventory);
+        to_json(&engine.search(&form))
+    }
+
+    fn armor_lists(&self) -> ArmorLists {
+        let talismans = get_talismans(&self.paths.data_file(TALISMANS_FILE)).unwrap_or_default();
+        let blacklist = get_blacklist(&self.paths.data_file(BLACKLIST_FILE)).unwrap_or_default();
+        ArmorLists::new(self.armors.clone(), talismans, &blacklist)
+    }
+
+    // no file yet = no saved build
+    fn saved_builds(&self) -> Result<HashMap<String, Build>, ApiError> {
+        let path = self.paths.data_file(BUILDS_FILE);
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        get_saved_builds(&path).map_err(|err| ApiError(500, err.to_string()))
+    }
+
+    fn put_build(&self, name: String, build: Build) -> ApiResult {
+        let mut builds = self.saved_builds()?;
+        builds.insert(name, build);
+        save_builds(&builds, &self.paths.data_file(BUILDS_FILE))
+            .map_err(|err| ApiError(500, err.to_string()))?;
+        to_json(&builds)
+    }
+
+    fn delete_build(&self, name: &str) -> ApiResult {
+        let mut builds = self.saved_builds()?;
+        if builds.remove(name).is_none() {
+            return Err(ApiError(404, format!("No build named {}", name)));
+        }
+        save_builds(&builds, &self.paths.data_file(BUILDS_FILE))
+            .map_err(|err| ApiError(500, err.to_string()))?;
+        to_json(&builds)
+    }
+
+    // in the format of the talismans file
+    fn talismans(&self) -> ApiResult {
+        let path = self.paths.data_file(TALISMANS_FILE);
+        let talismans = if path.exists() {
+            get_talismans(&path).map_err(|err| ApiError(500, err.to_string()))?
+        } else {
+            Vec::new()
+        };
+        let talismans: Vec<Talisman> = talismans.iter().map(armor_to_talisman).collect();
+        to_json(&talismans)
+    }
+
+    fn put_talismans(&self, talismans: Vec<Talisman>) -> ApiResult {
+        let talismans: Vec<Armor> = talismans.iter().map(talisman_to_armor).collect();
+        save_talismans_to_file(&talismans, &self.paths.data_file(TALISMANS_FILE))
+            .map_err(|err| ApiError(500, err.to_string()))?;
+        self.talismans()
+    }
+}
+
+fn read_json<T: DeserializeOwned>(request: &mut Request) -> Result<T, ApiError> {
+    let mut body = String::new();
+    // one more byte to know if the body is too long
+    request
+        .as_reader()
+        .take(MAX_BODY_SIZE + 1)
+        .read_to_string(&mut body)
+        .map_err(|err| ApiError(400, err.to_string()))?;
+    if body.len() as u64 > MAX_BODY_SIZE {
+        return Err(ApiError(413, "Body too long".to_string()));
+    }
+    serde_json::from_str(&body).map_err(|err| ApiError(400, format!("Invalid body: {}", err)))
+}
+
+fn to_json<T: Serialize>(value: &T) -> ApiResult {
+    serde_json::to_string(value).map_err(|err| ApiError(500, err.to_string()))
+}
+
+// The build names can have spaces or accents, "%20" and the like in the URL.
+fn percent_decode(text: &str) -> Result<String, ApiError> {
+    let invalid = || ApiError(400, format!("Invalid URL encoding: {}", text));
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = [
+                input.next().ok_or_else(invalid)?,
+                input.next().ok_or_else(invalid)?,
+            ];
+            // from_str_radix alone would accept a sign like "+1"
+            if !hex.iter().all(u8::is_ascii_hexdigit) {
+                return Err(invalid());
+            }
+            let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_names_are_percent_decoded() {
+        assert_eq!(
+            percent_decode("My%20build").ok().as_deref(),
+            Some("My build")
+        );
+        assert_eq!(
+            percent_decode("%C3%A9p%C3%A9e").ok().as_deref(),
+            Some("épée")
+        );
+        assert_eq!(percent_decode("plain").ok().as_deref(), Some("plain"));
+
+        for invalid in ["%2", "%zz", "%FF", "%+1", "%-1"] {
+            assert_eq!(percent_decode(invalid).err().map(|err| err.0), Some(400));
+        }
+    }
+}
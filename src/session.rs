@@ -0,0 +1,122 @@
+use std::{
+    fs::{canonicalize, write, File},
+    path::Path,
+};
+
+use rab_core::{armor_and_skills::Skill, build_search::Build};
+use ron::{
+    de::from_reader,
+    ser::{to_string_pretty, PrettyConfig},
+    Error,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::search_form::SearchForm;
+
+// What the user was doing when RAB was closed.
+// The armors are saved by name because the armor files can be updated.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    pub locked_armors: [Option<String>; 6], // same order as build_parts
+    pub form: Option<SearchForm>,           // None for the sessions of older versions
+    pub results: Option<SessionResults>,    // only if the user wants them back
+}
+
+// The builds are saved with their armors, they may be outdated after an update.
+#[derive(Serialize, Deserialize)]
+pub struct SessionResults {
+    pub wishes: Vec<(Skill, u8)>,
+    pub optional_wishes: Vec<(Skill, u8, u8)>,
+    #[serde(default)] // not saved by the older versions
+    pub weapon_slots: [u8; 3],
+    pub builds: Vec<Build>,
+}
+
+pub fn get_session(path: &Path) -> Result<Session, Error> {
+    from_reader(File::open(path)?)
+}
+
+pub fn save_session(session: &Session, path: &Path) -> Result<String, Error> {
+    let text = to_string_pretty(session, PrettyConfig::new().indentor("  ".to_string()))?;
+
+    write(path, text)?;
+
+    let path = canonicalize(path)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        paths::tests::unique_temp_dir,
+        search::tests::armor,
+        search_form::{FormWish, WishKind},
+    };
+
+    #[test]
+    fn saved_session_is_read_back() {
+        let dir = unique_temp_dir("session");
+        let path = dir.join("session.ron");
+        let mut locked_armors: [Option<String>; 6] = Default::default();
+        locked_armors[1] = Some("Rathian Mail".to_string());
+        let session = Session {
+            locked_armors,
+            form: Some(SearchForm {
+                wishes: vec![FormWish {
+                    skill: Skill::AttackBoost,
+                    level: 3,
+                    kind: WishKind::Optional,
+                    weight: 2,
+                }],
+                min_defense: Some(100),
+                ..Default::default()
+            }),
+            results: Some(SessionResults {
+                wishes: vec![(Skill::AttackBoost, 3)],
+                optional_wishes: vec![],
+                weapon_slots: [1, 0, 0],
+                builds: vec![Build {
+                    helmet: Some((
+                        armor("Rathian Helm", &[(Skill::AttackBoost, 1)], &[2]),
+                        [Some(Skill::AttackBoost), None, None],
+                    )),
+                    chest: None,
+                    arm: None,
+                    waist: None,
+                    leg: None,
+                    talisman: None,
+                    weapon_jewels: [None; 3],
+                }],
+            }),
+        };
+        save_session(&session, &path).unwrap();
+        let read = get_session(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read.locked_armors, session.locked_armors);
+        let form = read.form.unwrap();
+        assert_eq!(form.min_defense, Some(100));
+        let wish = form.wishes[0];
+        assert_eq!(
+            (wish.skill, wish.level, wish.kind, wish.weight),
+            (Skill::AttackBoost, 3, WishKind::Optional, 2)
+        );
+        let results = read.results.unwrap();
+        assert_eq!(results.wishes, [(Skill::AttackBoost, 3)]);
+        assert_eq!(results.weapon_slots, [1, 0, 0]);
+        let (helmet, jewels) = results.builds[0].helmet.as_ref().unwrap();
+        assert_eq!(helmet.name, "Rathian Helm");
+        assert_eq!(*jewels, [Some(Skill::AttackBoost), None, None]);
+    }
+
+    #[test]
+    fn older_sessions_have_no_form() {
+        let session: Session =
+            ron::from_str("(locked_armors: (None, None, None, None, None, None))").unwrap();
+        assert!(session.form.is_none());
+        assert!(session.results.is_none());
+    }
+}
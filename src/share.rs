@@ -0,0 +1,515 @@
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use rab_core::{
+    armor_and_skills::{armor_to_talisman, talisman_to_armor, Armor, Gender, Skill, Talisman},
+    build_search::{Build, Jewels},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_stats::{build_parts, place_jewels},
+    locale::InterfaceSymbol,
+    search_form::{FormWish, SearchForm, WishKind, MAX_WISH_WEIGHT},
+};
+
+// A share code is "RAB<version>-" then the build in RON, compressed
+// and encoded in URL-safe base64. The version must be increased
+// when SharedBuild changes, the older codes must still be read.
+const BUILD_CODE_PREFIX: &str = "RAB";
+const BUILD_CODE_VERSION: u32 = 1;
+// A build is less than 1 KiB of RON, a code that decompresses
+// to more than that is not made by RAB.
+const MAX_BUILD_TEXT_LENGTH: u64 = 16 * 1024;
+
+// A query code is the fragment of a URL, the web version reads the same one:
+//   v=1&w=AttackBoost-7,CriticalEye-4&o=Agitator-3-2&a=Guard-0&g=female&ws=3-1-0
+// w = required wishes (skill-level), o = optional wishes (skill-level-weight),
+// a = avoided skills (skill-highest allowed level), g = gender, ws = weapon slots.
+// The levels and the weights must be allowed by the sliders of the main page.
+// The skills are the variant names of rab-core. The unknown keys are ignored
+// so new keys can be added without a new version.
+const QUERY_CODE_VERSION: u32 = 1;
+// far more than every skill of the game with its level and weight
+const MAX_QUERY_CODE_LENGTH: usize = 8 * 1024;
+
+// The pieces are saved by name, the receiver has the same armor files.
+// The talismans are made by each player so they are saved entirely.
+#[derive(Serialize, Deserialize)]
+struct SharedBuild {
+    pieces: Vec<Option<(String, Jewels)>>, // helmet, chest, arm, waist, leg
+    talisman: Option<(Talisman, Jewels)>,
+    weapon_jewels: Jewels,
+}
+
+#[derive(Debug, Clone)]
+pub enum ShareCodeError {
+    Invalid,
+    UnknownVersion(u32), // made by a newer version of RAB
+    UnknownPieces(Vec<String>),
+    UnknownSkill(String),
+}
+
+impl Display for ShareCodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareCodeError::Invalid => write!(f, "{}", InterfaceSymbol::InvalidShareCode),
+            ShareCodeError::UnknownVersion(version) => write!(
+                f,
+                "{}",
+                InterfaceSymbol::TemplateUnknownShareCodeVersion
+                    .to_string()
+                    .replace("{version}", &version.to_string())
+            ),
+            ShareCodeError::UnknownPieces(names) => write!(
+                f,
+                "{}",
+                InterfaceSymbol::TemplateUnknownPieces
+                    .to_string()
+                    .replace("{pieces}", &names.join(", "))
+            ),
+            ShareCodeError::UnknownSkill(name) => write!(
+                f,
+                "{}",
+                InterfaceSymbol::TemplateUnknownSkill
+                    .to_string()
+                    .replace("{skill}", name)
+            ),
+        }
+    }
+}
+
+pub fn build_to_share_code(build: &Build) -> String {
+    let parts = build_parts(build);
+    let shared = SharedBuild {
+        pieces: parts[..5]
+            .iter()
+            .map(|part| {
+                part.as_ref()
+                    .map(|(armor, jewels)| (armor.name.clone(), *jewels))
+            })
+            .collect(),
+        talisman: build
+            .talisman
+            .as_ref()
+            .map(|(talisman, jewels)| (armor_to_talisman(talisman), *jewels)),
+        weapon_jewels: build.weapon_jewels,
+    };
+    // the build is made of names and skills, it can always be serialized
+    encode_build_text(&ron::to_string(&shared).unwrap())
+}
+
+fn encode_build_text(text: &str) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(text.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    format!(
+        "{}{}-{}",
+        BUILD_CODE_PREFIX,
+        BUILD_CODE_VERSION,
+        base64::encode_config(compressed, base64::URL_SAFE_NO_PAD)
+    )
+}
+
+// The pieces are searched in the lists of the same slot
+// (helmets, chests, arms, waists then legs).
+pub fn build_from_share_code(code: &str, armors: [&[Armor]; 5]) -> Result<Build, ShareCodeError> {
+    let (version, data) = code
+        .trim()
+        .strip_prefix(BUILD_CODE_PREFIX)
+        .and_then(|code| code.split_once('-'))
+        .ok_or(ShareCodeError::Invalid)?;
+    let version: u32 = version.parse().map_err(|_| ShareCodeError::Invalid)?;
+    if version > BUILD_CODE_VERSION {
+        return Err(ShareCodeError::UnknownVersion(version));
+    }
+
+    let compressed = base64::decode_config(data, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| ShareCodeError::Invalid)?;
+    let mut text = String::new();
+    DeflateDecoder::new(&compressed[..])
+        .take(MAX_BUILD_TEXT_LENGTH + 1)
+        .read_to_string(&mut text)
+        .map_err(|_| ShareCodeError::Invalid)?;
+    if text.len() as u64 > MAX_BUILD_TEXT_LENGTH {
+        return Err(ShareCodeError::Invalid);
+    }
+    let shared: SharedBuild = ron::from_str(&text).map_err(|_| ShareCodeError::Invalid)?;
+    if shared.pieces.len() != armors.len()
+        || !shared.talisman.as_ref().is_none_or(|(talisman, jewels)| {
+            is_valid_talisman(talisman) && jewels_fit(&talisman.slots, jewels)
+        })
+        || !jewels_exist(&shared.weapon_jewels)
+    {
+        return Err(ShareCodeError::Invalid);
+    }
+
+    let mut unknown = Vec::new();
+    let mut pieces: Vec<Option<(Armor, Jewels)>> = Vec::with_capacity(armors.len());
+    for (piece, list) in shared.pieces.into_iter().zip(armors) {
+        pieces.push(match piece {
+            Some((name, jewels)) => match list.iter().find(|armor| armor.name == name) {
+                Some(armor) if !jewels_fit(&armor.slots, &jewels) => {
+                    return Err(ShareCodeError::Invalid)
+                }
+                Some(armor) => Some((armor.clone(), jewels)),
+                None => {
+                    unknown.push(name);
+                    None
+                }
+            },
+            None => None,
+        });
+    }
+    if !unknown.is_empty() {
+        return Err(ShareCodeError::UnknownPieces(unknown));
+    }
+
+    let mut pieces = pieces.into_iter();
+    Ok(Build {
+        helmet: pieces.next().flatten(),
+        chest: pieces.next().flatten(),
+        arm: pieces.next().flatten(),
+        waist: pieces.next().flatten(),
+        leg: pieces.next().flatten(),
+        talisman: shared
+            .talisman
+            .map(|(talisman, jewels)| (talisman_to_armor(&talisman), jewels)),
+        weapon_jewels: shared.weapon_jewels,
+    })
+}
+
+// the talisman editor allows 3 slots and the skills up to their limit
+fn is_valid_talisman(talisman: &Talisman) -> bool {
+    talisman.slots.len() <= 3
+        && talisman.slots.iter().all(|size| (1..=3).contains(size))
+        && talisman
+            .skills
+            .iter()
+            .all(|(skill, level)| (1..=skill.get_limit()).contains(level))
+}
+
+fn jewels_exist(jewels: &Jewels) -> bool {
+    jewels
+        .iter()
+        .flatten()
+        .all(|skill| skill.get_jewel_size().is_some())
+}
+
+// the jewels are placed again to be shown, each one must have a slot
+fn jewels_fit(slots: &[u8], jewels: &Jewels) -> bool {
+    jewels_exist(jewels)
+        && place_jewels(slots, jewels)
+            .iter()
+            .filter(|(_, skill)| skill.is_some())
+            .count()
+            == jewels.iter().flatten().count()
+}
+
+// only the wishes, the gender and the weapon slots are shared
+pub fn form_to_query_code(form: &SearchForm) -> String {
+    let wishes = |kind: WishKind| {
+        form.wishes
+            .iter()
+            .filter(|wish| wish.kind == kind)
+            .map(|wish| match kind {
+                WishKind::Optional => format!("{:?}-{}-{}", wish.skill, wish.level, wish.weight),
+                _ => format!("{:?}-{}", wish.skill, wish.level),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let gender = match form.gender {
+        Gender::Female => "female",
+        Gender::Male => "male",
+    };
+    let weapon_slots = form.weapon_slots.map(|size| size.to_string()).join("-");
+
+    format!(
+        "v={}&w={}&o={}&a={}&g={}&ws={}",
+        QUERY_CODE_VERSION,
+        wishes(WishKind::Required),
+        wishes(WishKind::Optional),
+        wishes(WishKind::Avoid),
+        gender,
+        weapon_slots
+    )
+}
+
+// The whole URL can be pasted, only the fragment is read.
+// The other fields of the form are kept.
+pub fn apply_query_code(code: &str, form: &mut SearchForm) -> Result<(), ShareCodeError> {
+    let code = code.trim();
+    if code.len() > MAX_QUERY_CODE_LENGTH {
+        return Err(ShareCodeError::Invalid);
+    }
+    let fragment = code.rsplit_once('#').map_or(code, |(_, fragment)| fragment);
+
+    let mut version = None;
+    let mut wishes = Vec::new();
+    let mut gender = form.gender;
+    let mut weapon_slots = form.weapon_slots;
+    for pair in fragment.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').ok_or(ShareCodeError::Invalid)?;
+        match key {
+            "v" => version = Some(value.parse().map_err(|_| ShareCodeError::Invalid)?),
+            "w" => wishes.extend(parse_wishes(value, WishKind::Required)?),
+            "o" => wishes.extend(parse_wishes(value, WishKind::Optional)?),
+            "a" => wishes.extend(parse_wishes(value, WishKind::Avoid)?),
+            "g" => {
+                gender = match value {
+                    "female" => Gender::Female,
+                    "male" => Gender::Male,
+                    _ => return Err(ShareCodeError::Invalid),
+                }
+            }
+            "ws" => {
+                let sizes: Vec<u8> = value
+                    .split('-')
+                    .map(|size| size.parse().ok().filter(|size| *size <= 3))
+                    .collect::<Option<_>>()
+                    .ok_or(ShareCodeError::Invalid)?;
+                weapon_slots = sizes.try_into().map_err(|_| ShareCodeError::Invalid)?;
+            }
+            _ => (),
+        }
+    }
+    match version {
+        Some(version) if version > QUERY_CODE_VERSION => {
+            return Err(ShareCodeError::UnknownVersion(version))
+        }
+        Some(_) => (),
+        None => return Err(ShareCodeError::Invalid),
+    }
+
+    form.wishes = wishes;
+    form.gender = gender;
+    form.weapon_slots = weapon_slots;
+    Ok(())
+}
+
+fn parse_wishes(value: &str, kind: WishKind) -> Result<Vec<FormWish>, ShareCodeError> {
+    value
+        .split(',')
+        .filter(|wish| !wish.is_empty())
+        .map(|wish| {
+            let mut fields = wish.split('-');
+            let name = fields.next().unwrap_or_default();
+            let skill = Skill::ALL
+                .iter()
+                .copied()
+                .find(|skill| format!("{:?}", skill) == name)
+                .ok_or_else(|| ShareCodeError::UnknownSkill(name.to_string()))?;
+            let mut number = |default: Option<u8>| match fields.next() {
+                Some(number) => number.parse().map_err(|_| ShareCodeError::Invalid),
+                None => default.ok_or(ShareCodeError::Invalid),
+            };
+            let level = number(None)?;
+            // the levels and the weights of the sliders
+            let min_level = if kind == WishKind::Avoid { 0 } else { 1 };
+            if !(min_level..=skill.get_limit()).contains(&level) {
+                return Err(ShareCodeError::Invalid);
+            }
+            let weight = match kind {
+                WishKind::Optional => number(Some(1))?,
+                _ => 1,
+            };
+            if !(1..=MAX_WISH_WEIGHT).contains(&weight) || fields.next().is_some() {
+                return Err(ShareCodeError::Invalid);
+            }
+            Ok(FormWish {
+                skill,
+                level,
+                kind,
+                weight,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::tests::armor;
+
+    fn helmets() -> Vec<Armor> {
+        vec![armor("Helm", &[(Skill::AttackBoost, 1)], &[2])]
+    }
+
+    fn decode(code: &str) -> Result<Build, ShareCodeError> {
+        let helmets = helmets();
+        build_from_share_code(code, [&helmets, &[], &[], &[], &[]])
+    }
+
+    #[test]
+    fn shared_build_is_read_back() {
+        let build = Build {
+            helmet: Some((helmets()[0].clone(), [Some(Skill::CriticalEye), None, None])),
+            chest: None,
+            arm: None,
+            waist: None,
+            leg: None,
+            talisman: Some((armor("Talisman", &[(Skill::Botanist, 2)], &[1]), [None; 3])),
+            weapon_jewels: [Some(Skill::Botanist), None, None],
+        };
+        let read = decode(&build_to_share_code(&build)).unwrap();
+        let (helmet, jewels) = read.helmet.unwrap();
+        assert_eq!(helmet.name, "Helm");
+        assert_eq!(jewels, [Some(Skill::CriticalEye), None, None]);
+        assert_eq!(read.talisman.unwrap().0.skills, [(Skill::Botanist, 2)]);
+        assert_eq!(read.weapon_jewels, build.weapon_jewels);
+    }
+
+    #[test]
+    fn wrong_build_codes_are_rejected() {
+        let piece = |jewels: &str| {
+            format!(
+                "(pieces: [Some((\"Helm\", {})), None, None, None, None], talisman: None, weapon_jewels: (None, None, None))",
+                jewels
+            )
+        };
+        let talisman = |talisman: &str| {
+            format!(
+                "(pieces: [None, None, None, None, None], talisman: Some(({}, (None, None, None))), weapon_jewels: (None, None, None))",
+                talisman
+            )
+        };
+        // the text is valid, only the jewels and the talismans are wrong
+        assert!(decode(&encode_build_text(&piece("(None, None, None)"))).is_ok());
+
+        for code in [
+            "hello".to_string(),
+            "RAB1-".to_string(),
+            "RAB1-not base64".to_string(),
+            encode_build_text("not ron"),
+            // a jewel too big for the slot, then two jewels for one slot
+            encode_build_text(&piece("(Some(Handicraft), None, None)")),
+            encode_build_text(&piece("(Some(CriticalEye), Some(Botanist), None)")),
+            // a skill without jewel
+            encode_build_text(&piece("(Some(WindAlignment), None, None)")),
+            encode_build_text(&talisman(
+                "(name: \"T\", skills: [(AttackBoost, 8)], slots: [])",
+            )),
+            encode_build_text(&talisman(
+                "(name: \"T\", skills: [(AttackBoost, 0)], slots: [])",
+            )),
+            encode_build_text(&talisman("(name: \"T\", skills: [], slots: [4])")),
+            encode_build_text(&talisman("(name: \"T\", skills: [], slots: [1, 1, 1, 1])")),
+            // compresses well but is too long to be a build
+            encode_build_text(&format!(
+                "{}{}",
+                piece("(None, None, None)"),
+                " ".repeat(MAX_BUILD_TEXT_LENGTH as usize)
+            )),
+        ] {
+            assert!(
+                matches!(decode(&code), Err(ShareCodeError::Invalid)),
+                "{}",
+                code
+            );
+        }
+
+        assert!(matches!(
+            decode("RAB2-AAAA"),
+            Err(ShareCodeError::UnknownVersion(2))
+        ));
+        assert!(matches!(
+            decode(&encode_build_text(&piece("(None, None, None)").replace("Helm", "Mail"))),
+            Err(ShareCodeError::UnknownPieces(names)) if names == ["Mail"]
+        ));
+    }
+
+    #[test]
+    fn query_code_is_read_back() {
+        let mut form = SearchForm {
+            wishes: vec![
+                FormWish {
+                    skill: Skill::AttackBoost,
+                    level: 7,
+                    kind: WishKind::Required,
+                    weight: 1,
+                },
+                FormWish {
+                    skill: Skill::Agitator,
+                    level: 3,
+                    kind: WishKind::Optional,
+                    weight: MAX_WISH_WEIGHT,
+                },
+                FormWish {
+                    skill: Skill::Guard,
+                    level: 0,
+                    kind: WishKind::Avoid,
+                    weight: 1,
+                },
+            ],
+            gender: Gender::Female,
+            weapon_slots: [3, 1, 0],
+            ..Default::default()
+        };
+        let code = format!("https://example.com/rab#{}", form_to_query_code(&form));
+
+        let mut read = SearchForm::default();
+        apply_query_code(&code, &mut read).unwrap();
+        let wishes = |form: &SearchForm| {
+            form.wishes
+                .iter()
+                .map(|wish| (wish.skill, wish.level, wish.kind, wish.weight))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(wishes(&read), wishes(&form));
+        assert!(read.gender == Gender::Female);
+        assert_eq!(read.weapon_slots, [3, 1, 0]);
+
+        // a missing weight is the default one
+        form.wishes.clear();
+        apply_query_code("v=1&o=Agitator-3", &mut form).unwrap();
+        assert_eq!(form.wishes[0].weight, 1);
+    }
+
+    #[test]
+    fn wrong_query_codes_are_rejected() {
+        for code in [
+            "w=AttackBoost-7",
+            "v=1&w=AttackBoost",
+            "v=1&w=AttackBoost-0",
+            "v=1&w=AttackBoost-8",
+            "v=1&w=AttackBoost-7-2",
+            "v=1&o=Agitator-3-0",
+            "v=1&o=Agitator-3-11",
+            "v=1&o=Agitator-3-2-1",
+            "v=1&a=WeaknessExploit-4",
+            "v=1&g=other",
+            "v=1&ws=4-0-0",
+            "v=1&ws=1-1",
+        ] {
+            let mut form = SearchForm::default();
+            assert!(
+                matches!(
+                    apply_query_code(code, &mut form),
+                    Err(ShareCodeError::Invalid)
+                ),
+                "{}",
+                code
+            );
+        }
+
+        let mut form = SearchForm::default();
+        let long = format!("v=1&w={}", "AttackBoost-1,".repeat(1000));
+        assert!(matches!(
+            apply_query_code(&long, &mut form),
+            Err(ShareCodeError::Invalid)
+        ));
+        assert!(matches!(
+            apply_query_code("v=1&w=Unknown-1", &mut form),
+            Err(ShareCodeError::UnknownSkill(name)) if name == "Unknown"
+        ));
+        assert!(matches!(
+            apply_query_code("v=2", &mut form),
+            Err(ShareCodeError::UnknownVersion(2))
+        ));
+    }
+}
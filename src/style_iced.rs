@@ -1,16 +1,7 @@
 use iced::{button, container, Background, Color, Vector};
 
-#[derive(PartialEq, Clone, Copy)]
-pub enum Theme {
-    Dark,
-    Light,
-}
-
-impl Default for Theme {
-    fn default() -> Self {
-        Theme::Dark
-    }
-}
+// the theme is a profile setting, the headless builds need it without iced
+pub use rab::profile::Theme;
 
 pub enum Container {
     Fire,
@@ -19,6 +10,7 @@ pub enum Container {
     Water,
     Dragon,
     Defense,
+    Warning,
     Talisman(Theme),
     DarkTheme,
 }
@@ -61,6 +53,12 @@ impl container::StyleSheet for Container {
                 background: Some(Background::Color(Color::from_rgb(0.7, 0.7, 0.7))),
                 ..container::Style::default()
             },
+            Container::Warning => container::Style {
+                text_color: Some(Color::WHITE),
+                background: Some(Background::Color(Color::from_rgb(0.95, 0.27, 0.41))),
+                border_radius: 10.0,
+                ..container::Style::default()
+            },
             Container::Talisman(theme) => match theme {
                 Theme::Dark => container::Style {
                     border_radius: 10.0,
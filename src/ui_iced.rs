@@ -1,42 +1,51 @@
+mod additional_skills_page;
+mod blacklist_page;
 mod builds_page;
 mod common_elements;
 mod details_page;
+mod diagnosis_page;
+mod jewels_page;
 mod lang_page;
 mod main_page;
 mod no_files_page;
 mod talisman_page;
 
 use self::{
-    builds_page::BuildsPage, details_page::DetailsPage, lang_page::LangPage, main_page::MainPage,
-    no_files_page::NoFilesPage, talisman_page::TalismanPage,
+    additional_skills_page::AdditionalSkillsPage, blacklist_page::BlacklistPage,
+    builds_page::BuildsPage, details_page::DetailsPage, diagnosis_page::DiagnosisPage,
+    jewels_page::JewelsPage, lang_page::LangPage, main_page::MainPage, no_files_page::NoFilesPage,
+    talisman_page::TalismanPage,
 };
-use crate::{
-    file::{get_armor_list, get_talismans, save_talismans_to_file},
-    locale::{get_locales, Locale, LocalizedSkill},
-    profile::{get_profile, save_profile},
-    style_iced,
+use crate::style_iced;
+use iced::{clipboard, executor, pure, Command, Length, Subscription};
+use iced_native::{subscription, window, Event};
+use rab::{
+    app_state::{skill_totals, AppState, Messages, TalismanDraft},
+    build_stats::{ArmorSlot, BuildSort},
+    diagnosis::{run_diagnosis, Diagnosis},
+    export::{export_build, ExportFormat},
+    locale::LocalizedSkill,
+    paths::Paths,
+    search::{
+        find_max_additional_level, find_max_wish_level, run_search_step, SearchJob,
+        StatRequirements,
+    },
+    search_form::{is_valid_preset_name, rename_preset, FormWish, SearchForm, WishKind},
+    share::{apply_query_code, build_to_share_code, form_to_query_code},
     update::download_armors_and_locales,
-    ARMORS_PATH, LOCALE_DIR_PATH,
 };
-use iced::{executor, pure, Command, Length};
 use rab_core::{
     armor_and_skills::{Armor, Gender, Skill},
-    build_search::{pre_selection_then_brute_force_search, AllArmorSlices, Build},
-};
-use ron::{
-    de::from_reader,
-    ser::{to_string_pretty, PrettyConfig},
-    Error,
-};
-use std::{
-    collections::HashMap,
-    fs::{self, canonicalize},
-    path::Path,
+    build_search::Build,
 };
+use std::sync::Arc;
 
+// the talisman editor only uses the skill and the level
 struct WishField {
     selected: LocalizedSkill,
     value_slider: u8,
+    kind: WishKind,
+    weight: u8,
 }
 
 impl Default for WishField {
@@ -44,12 +53,25 @@ impl Default for WishField {
         Self {
             value_slider: 1,
             selected: Default::default(),
+            kind: WishKind::Required,
+            weight: 1,
         }
     }
 }
 
+// given by the command line
+#[derive(Default)]
+pub struct Flags {
+    pub paths: Paths,
+    pub lang: Option<String>,
+    pub theme: Option<style_iced::Theme>,
+    pub offline: bool,
+}
+
 #[derive(Default)]
 pub struct MainApp {
+    state: AppState,
+
     wish_fields: Vec<WishField>,
 
     value_filter_text_input: String,
@@ -57,23 +79,15 @@ pub struct MainApp {
     sorted_wish_choices: Vec<LocalizedSkill>,
     filtered_wish_choices: Vec<LocalizedSkill>,
 
-    helmets: Vec<Armor>,
-    chests: Vec<Armor>,
-    arms: Vec<Armor>,
-    waists: Vec<Armor>,
-    legs: Vec<Armor>,
-
-    talismans: Vec<Armor>,
-
-    builds: Vec<Build>,
-
     armor_desc: Option<(Armor, [Option<Skill>; 3])>,
+    armor_desc_slot: Option<ArmorSlot>,
 
     page: Page,
 
     selected_gender: Gender,
 
     states_values_slider_weapon_slot: [u8; 3],
+    states_values_slider_free_slot: [u8; 3], // index 0 = level 1
     selected_talisman: Option<usize>,
 
     is_editing: bool,
@@ -83,53 +97,91 @@ pub struct MainApp {
 
     edit_wish_fields: Vec<WishField>,
 
-    locales: HashMap<String, Locale>,
-    selected_locale: String,
-
-    profile: HashMap<String, String>,
-
     theme: style_iced::Theme,
 
     update_state: UpdateState,
 
-    details_build_index: usize,
-
-    saved_builds: HashMap<String, Build>,
-
     details_build_name: String,
 
     focused_build: Option<Build>,
     total_skills_and_amounts_focused_build: Vec<(Skill, u8)>, // to not sort everytime
+
+    value_share_code_text_input: String,
+    share_code_error: Option<String>,
+
+    value_query_code_text_input: String,
+    query_code_error: Option<String>,
+
+    running_search: Option<RunningSearch>,
+    last_search_id: u64,
+
+    value_min_defense_text_input: String,
+    values_min_resistance_text_input: [String; 5],
+
+    running_additional_skills: Option<RunningAdditionalSkills>,
+    additional_skills: Vec<(Skill, u8)>,
+
+    running_diagnosis: Option<(u64, Arc<SearchJob>)>, // search id
+    diagnosis: Option<Diagnosis>,
+
+    selected_preset: Option<String>,
+    value_preset_name_text_input: String,
+
+    running_max_levels: Option<(u64, Arc<SearchJob>)>, // search id
+    wish_max_levels: Vec<Option<u8>>,                  // same order as the required wishes
+
+    should_exit: bool,
+}
+
+struct RunningSearch {
+    id: u64,
+    job: Arc<SearchJob>,
+    done_steps: usize,
+}
+
+impl RunningSearch {
+    fn progress(&self) -> f32 {
+        self.done_steps as f32 / self.job.step_count() as f32
+    }
+}
+
+// one search per candidate skill
+struct RunningAdditionalSkills {
+    id: u64,
+    job: Arc<SearchJob>,
+    candidates: Vec<Skill>,
+    done_steps: usize,
 }
 
-#[derive(Clone, Copy)]
+impl RunningAdditionalSkills {
+    fn progress(&self) -> f32 {
+        self.done_steps as f32 / self.candidates.len() as f32
+    }
+}
+
+#[derive(Default, Clone, Copy)]
 enum UpdateState {
     Updating,
     Done,
+    #[default]
     Initial,
     Problem,
+    Offline, // the update can't be started
 }
 
-impl Default for UpdateState {
-    fn default() -> Self {
-        UpdateState::Initial
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy)]
 pub enum Page {
+    #[default]
     Main,
     Talisman,
     Lang,
     NoFiles,
     Details(bool), // true check saved builds
     Builds,
-}
-
-impl Default for Page {
-    fn default() -> Self {
-        Page::Main
-    }
+    Blacklist,
+    Jewels,
+    AdditionalSkills,
+    Diagnosis,
 }
 
 #[derive(Debug, Clone)]
@@ -138,11 +190,42 @@ pub enum Msg {
     AddWish,
     RemoveWish(usize),
     SliderChanged(usize, u8),
+    WishKindChanged(usize, WishKind),
+    PresetSelected(String),
+    PresetNameChanged(String),
+    SavePreset,
+    RenamePreset,
+    DeletePreset,
+    WishWeightChanged(usize, u8),
     Search,
-    ArmorDesc(Option<(Armor, [Option<Skill>; 3])>),
+    SearchStepDone(u64, usize, Option<Vec<Build>>), // search id, step, found builds
+    CancelSearch,
+    FindAdditionalSkills,
+    AdditionalSkillStepDone(u64, usize, Option<u8>), // search id, candidate index, max level
+    CancelAdditionalSkills,
+    Diagnose,
+    DiagnosisDone(u64, Option<Diagnosis>), // search id
+    CancelDiagnosis,
+    MaxWishLevelDone(u64, usize, Option<u8>), // search id, wish index, max level
+    SortChanged(BuildSort),
+    ArmorDesc(ArmorSlot, Option<(Armor, [Option<Skill>; 3])>),
+    LockArmor(ArmorSlot, Armor),
+    UnlockArmor(ArmorSlot),
+    ExcludeArmor(String),
+    ExcludeFamily(String),
+    RemoveExcludedArmor(String),
+    RemoveExcludedFamily(String),
+    JewelAmountChanged(Skill, u8),
+    SaveJewels,
+    DiscardJewels,
+    ToggleIgnoreJewelInventory(bool),
+    ToggleRestoreResults(bool),
     FilterChanged(String),
     GenderChanged(Gender),
     WeaponSlotChanged(usize, u8),
+    FreeSlotChanged(usize, u8), // index 0 = level 1
+    MinDefenseChanged(String),
+    MinResistanceChanged(usize, String),
     SelectTalisman(Option<usize>),
     EditTalisman,
     SaveEdition,
@@ -165,22 +248,21 @@ pub enum Msg {
     DownloadArmors,
     DownloadDone(bool),
     BuildDetails(usize), // index of build in vec builds
-    SaveBuild(usize),
+    SaveBuild,
     SavedBuildDetails(String), // index of build in vec builds
     EditSavedBuild(String),
     RemoveSavedBuild(String),
+    CopyShareCode,
+    ShareCodeChanged(String),
+    ImportShareCode,
+    CopyQueryCode,
+    QueryCodeChanged(String),
+    ImportQueryCode,
+    CopyExport(ExportFormat),
+    ExportSavedBuilds(ExportFormat),
+    CloseRequested,
 }
 
-const WAISTS_PATH: &str = "armors/waists.ron";
-const HELMETS_PATH: &str = "armors/helmets.ron";
-const ARMS_PATH: &str = "armors/arms.ron";
-const LEGS_PATH: &str = "armors/legs.ron";
-const CHESTS_PATH: &str = "armors/chests.ron";
-
-const TALISMANS_PATH: &str = "talismans.ron";
-const PROFILE_PATH: &str = "profile.ron";
-const BUILDS_PATH: &str = "builds.ron";
-
 impl MainApp {
     fn clear_talisman_editor(&mut self) {
         for slider_value in self.states_values_slider_talisman_slot.iter_mut() {
@@ -189,219 +271,185 @@ impl MainApp {
         self.edit_wish_fields.clear();
     }
 
-    fn save_profile(&self) {
-        match save_profile(&self.profile, PROFILE_PATH) {
-            Ok(file) => println!("Profile saved to {}", file),
-            Err(err) => println!("Can't save profile:\n{}", err),
-        };
-    }
-
-    fn reload_locales(&mut self) {
-        self.locales = match get_locales(LOCALE_DIR_PATH) {
-            Ok(locales) => locales,
-            Err(err) => {
-                println!(
-                    "Error with localization files at {}\n{}\nWARNING: no locale loaded.",
-                    LOCALE_DIR_PATH, err
-                );
-                HashMap::with_capacity(0)
-            }
-        };
-
-        *super::LOCALE.lock().unwrap() = self.locales.get(&self.selected_locale).cloned();
+    fn save_session(&self) {
+        self.state.save_session(self.search_form())
     }
 
-    fn reload_armors(&mut self) {
-        let (helmets, chests, arms, waists, legs) = match get_all_armors_from_file() {
-            Ok(lists) => lists,
-            Err(err) => {
-                println!("ERROR: Can't reload armors:\n{}", err);
-                (vec![], vec![], vec![], vec![], vec![])
-            }
-        };
-        self.helmets = helmets;
-        self.chests = chests;
-        self.arms = arms;
-        self.waists = waists;
-        self.legs = legs;
+    // Must be called each time the search form changes.
+    // The session is only saved by a search and on exit, not at each keystroke.
+    fn form_changed(&mut self) -> Command<Msg> {
+        self.compute_max_levels()
     }
 
-    fn save_builds(&self) {
-        match save_builds(&self.saved_builds, BUILDS_PATH) {
-            Ok(path) => println!("Builds saved to {}", path),
-            Err(err) => println!("Unable to save builds:\n{}", err),
+    fn cancel_search(&mut self) {
+        if let Some(search) = self.running_search.take() {
+            search.job.cancel();
         }
     }
 
-    fn focus_new_build(&mut self, build: Build) {
-        self.total_skills_and_amounts_focused_build =
-            build.get_all_skills_and_amounts().drain().collect();
-        self.total_skills_and_amounts_focused_build
-            .sort_unstable_by_key(|(_, amount)| *amount);
-        self.focused_build = Some(build);
-    }
-}
+    // The max levels are computed one wish after another.
+    // Must be called each time the wishes or the other requirements change.
+    fn compute_max_levels(&mut self) -> Command<Msg> {
+        if let Some((_, job)) = self.running_max_levels.take() {
+            job.cancel();
+        }
+        let required_count = self
+            .wish_fields
+            .iter()
+            .filter(|wish| wish.kind == WishKind::Required)
+            .count();
+        self.wish_max_levels = vec![None; required_count];
+        if required_count == 0 {
+            return Command::none();
+        }
 
-fn get_all_armors_from_file() -> Result<AllArmors, ron::Error> {
-    Ok((
-        get_armor_list(HELMETS_PATH)?,
-        get_armor_list(CHESTS_PATH)?,
-        get_armor_list(ARMS_PATH)?,
-        get_armor_list(WAISTS_PATH)?,
-        get_armor_list(LEGS_PATH)?,
-    ))
-}
+        let job = Arc::new(self.state.search_job(&self.search_form()));
+        self.last_search_id += 1;
+        let id = self.last_search_id;
+        self.running_max_levels = Some((id, job.clone()));
 
-type AllArmors = (Vec<Armor>, Vec<Armor>, Vec<Armor>, Vec<Armor>, Vec<Armor>);
+        Command::perform(find_max_wish_level(job, 0), move |level| {
+            Msg::MaxWishLevelDone(id, 0, level)
+        })
+    }
 
-fn create_locale_and_armors_dir() {
-    let armors_path = Path::new(ARMORS_PATH);
-    if !armors_path.is_dir() {
-        if let Err(err) = fs::create_dir(armors_path) {
-            println!("Can't create armors directory:\n{}", err)
+    fn cancel_diagnosis(&mut self) {
+        if let Some((_, job)) = self.running_diagnosis.take() {
+            job.cancel();
         }
     }
-    let locale_path = Path::new(LOCALE_DIR_PATH);
-    if !locale_path.is_dir() {
-        if let Err(err) = fs::create_dir(locale_path) {
-            println!("Can't create locale directory:\n{}", err)
+
+    fn cancel_additional_skills(&mut self) {
+        if let Some(search) = self.running_additional_skills.take() {
+            search.job.cancel();
         }
     }
-}
 
-use lexical_sort::natural_lexical_cmp;
+    fn stat_requirements(&self) -> StatRequirements {
+        StatRequirements {
+            defense: self.value_min_defense_text_input.parse().ok(),
+            resistances: [0, 1, 2, 3, 4]
+                .map(|index| self.values_min_resistance_text_input[index].parse().ok()),
+        }
+    }
 
-fn save_builds(builds: &HashMap<String, Build>, path: &str) -> Result<String, Error> {
-    let text = to_string_pretty(builds, PrettyConfig::new().indentor("  ".to_string()))?;
+    fn search_form(&self) -> SearchForm {
+        let requirements = self.stat_requirements();
+        SearchForm {
+            wishes: self
+                .wish_fields
+                .iter()
+                .map(|wish| FormWish {
+                    skill: wish.selected.0,
+                    level: wish.value_slider,
+                    kind: wish.kind,
+                    weight: wish.weight,
+                })
+                .collect(),
+            gender: self.selected_gender,
+            weapon_slots: self.states_values_slider_weapon_slot,
+            free_slots: self.states_values_slider_free_slot,
+            min_defense: requirements.defense,
+            min_resistances: requirements.resistances,
+            skill_filter: self.value_filter_text_input.clone(),
+        }
+    }
 
-    fs::write(path, text)?;
+    fn apply_search_form(&mut self, form: SearchForm) {
+        self.wish_fields = form
+            .wishes
+            .into_iter()
+            .map(|wish| WishField {
+                selected: LocalizedSkill(wish.skill),
+                value_slider: wish.level,
+                kind: wish.kind,
+                weight: wish.weight,
+            })
+            .collect();
+        // the main page always shows one wish at least
+        if self.wish_fields.is_empty() {
+            self.wish_fields.push(WishField::default());
+        }
+        self.selected_gender = form.gender;
+        self.states_values_slider_weapon_slot = form.weapon_slots;
+        self.states_values_slider_free_slot = form.free_slots;
+        self.value_min_defense_text_input = form
+            .min_defense
+            .map_or_else(String::new, |defense| defense.to_string());
+        self.values_min_resistance_text_input = form
+            .min_resistances
+            .map(|resistance| resistance.map_or_else(String::new, |value| value.to_string()));
+        self.filter_wish_choices(form.skill_filter);
+    }
 
-    let path = canonicalize(path)?;
+    fn filter_wish_choices(&mut self, filter: String) {
+        self.value_filter_text_input = filter;
+        self.filtered_wish_choices = self
+            .sorted_wish_choices
+            .iter()
+            .copied()
+            .filter(|skill| {
+                skill
+                    .to_string()
+                    .to_lowercase()
+                    .contains(&self.value_filter_text_input.to_lowercase())
+            })
+            .collect();
+    }
 
-    Ok(path.to_string_lossy().into_owned())
+    fn focus_new_build(&mut self, build: Build) {
+        self.total_skills_and_amounts_focused_build = skill_totals(&build);
+        self.focused_build = Some(build);
+    }
 }
 
-fn get_saved_builds(path: &str) -> Result<HashMap<String, Build>, Error> {
-    from_reader(fs::File::open(path)?)
-}
+use lexical_sort::natural_lexical_cmp;
 
 impl pure::Application for MainApp {
     type Message = Msg;
     type Executor = executor::Default;
-    type Flags = ();
-
-    fn new(_flags: Self::Flags) -> (Self, Command<Msg>) {
-        // let text = iced_futures::futures::executor::block_on(get_arms());
-        // println!("{}", text);
-        let talismans = match get_talismans(TALISMANS_PATH) {
-            Ok(talismans) => {
-                println!("Talisman file succesfully loaded.");
-                talismans
-            }
-            Err(err) => {
-                println!(
-                    "Can't read the talisman file: {}\nEmpty talisman list loaded.",
-                    err
-                );
-                vec![]
-            }
-        };
-
-        let mut page = Page::Main;
-        let (helmets, chests, arms, waists, legs) = match get_all_armors_from_file() {
-            Ok(lists) => lists,
-            Err(_) => {
-                page = Page::NoFiles;
-                (vec![], vec![], vec![], vec![], vec![])
-            }
-        };
+    type Flags = Flags;
 
-        let locales = match get_locales(LOCALE_DIR_PATH) {
-            Ok(locales) => locales,
-            Err(err) => {
-                println!(
-                    "Error with localization files at {}\n{}\nWARNING: no locale loaded.",
-                    LOCALE_DIR_PATH, err
-                );
-                HashMap::with_capacity(0)
-            }
-        };
-
-        let profile = match get_profile(PROFILE_PATH) {
-            Ok(map) => {
-                println!("Profile file succesfully loaded.");
-                map
-            }
-            Err(err) => {
-                println!(
-                    "Can't read the profile file:\n{}\nEmpty profile loaded.",
-                    err
-                );
-                Default::default()
-            }
-        };
-
-        let selected_locale = profile
-            .get("lang")
-            .cloned()
-            .unwrap_or_else(|| "English".to_string());
-
-        let theme = match profile.get("theme").unwrap_or(&"dark".to_string()).as_str() {
-            "light" => style_iced::Theme::Light,
-            _ => style_iced::Theme::Dark,
-        };
-
-        *super::LOCALE.lock().unwrap() = locales.get(&selected_locale).cloned();
-
-        let mut sorted_wish_choices: Vec<LocalizedSkill> =
-            Skill::ALL.iter().map(|s| LocalizedSkill(*s)).collect();
-        sorted_wish_choices
-            .sort_unstable_by(|a, b| natural_lexical_cmp(&a.to_string(), &b.to_string()));
+    fn new(flags: Self::Flags) -> (Self, Command<Msg>) {
+        let (state, form) = AppState::load(flags.paths, flags.lang, Messages::default());
 
+        let sorted_wish_choices = state.sorted_skills();
         let filtered_wish_choices = sorted_wish_choices.clone();
 
-        let saved_builds = match get_saved_builds(BUILDS_PATH) {
-            Ok(map) => {
-                println!("Builds file succesfully loaded.");
-                map
-            }
-            Err(err) => {
-                println!("Can't read the builds file:\n{}\nNo builds loaded.", err);
-                Default::default()
-            }
-        };
+        let mut app = Self {
+            wish_fields: vec![WishField::default()],
 
-        (
-            Self {
-                wish_fields: vec![WishField::default()],
+            filtered_wish_choices,
+            sorted_wish_choices,
 
-                waists,
-                helmets,
-                arms,
-                legs,
-                chests,
-                talismans,
+            selected_gender: Gender::Female,
 
-                filtered_wish_choices,
-                sorted_wish_choices,
+            page: if state.armors_missing {
+                Page::NoFiles
+            } else {
+                Page::Main
+            },
 
-                selected_gender: Gender::Female,
+            theme: flags.theme.unwrap_or(state.profile.theme),
 
-                page,
+            update_state: if flags.offline {
+                UpdateState::Offline
+            } else {
+                UpdateState::Initial
+            },
 
-                locales,
-                selected_locale,
+            state,
 
-                profile,
+            ..Self::default()
+        };
 
-                theme,
+        if let Some(form) = form {
+            app.apply_search_form(form);
+        }
 
-                saved_builds,
+        let command = app.compute_max_levels();
 
-                ..Self::default()
-            },
-            Command::none(),
-        )
+        (app, command)
     }
 
     fn title(&self) -> String {
@@ -411,94 +459,344 @@ impl pure::Application for MainApp {
     fn update(&mut self, message: Msg) -> Command<Msg> {
         match message {
             Msg::WishSelected(key, wish) => {
-                self.wish_fields[key].selected = wish;
-                self.wish_fields[key].value_slider = 1
+                let wish_field = &mut self.wish_fields[key];
+                wish_field.selected = wish;
+                wish_field.value_slider = if wish_field.kind == WishKind::Avoid {
+                    0
+                } else {
+                    1
+                };
+                return self.form_changed();
+            }
+            Msg::AddWish => {
+                self.wish_fields.push(WishField::default());
+                return self.form_changed();
             }
-            Msg::AddWish => self.wish_fields.push(WishField::default()),
             Msg::RemoveWish(index) => {
                 self.wish_fields.remove(index);
+                return self.form_changed();
+            }
+            Msg::SliderChanged(index, value) => {
+                self.wish_fields[index].value_slider = value;
+                return self.form_changed();
+            }
+            Msg::WishKindChanged(index, kind) => {
+                let wish = &mut self.wish_fields[index];
+                // an avoided skill is dropped by default, a wished one needs a level
+                match kind {
+                    WishKind::Avoid => wish.value_slider = 0,
+                    _ if wish.kind == WishKind::Avoid => wish.value_slider = 1,
+                    _ => (),
+                }
+                wish.kind = kind;
+                return self.form_changed();
+            }
+            Msg::WishWeightChanged(index, weight) => self.wish_fields[index].weight = weight,
+            Msg::PresetSelected(name) => {
+                let form = match self.state.presets.get(&name) {
+                    Some(form) => form.clone(),
+                    None => return Command::none(),
+                };
+                self.value_preset_name_text_input = name.clone();
+                self.selected_preset = Some(name);
+                self.apply_search_form(form);
+                return self.form_changed();
+            }
+            Msg::PresetNameChanged(name) => self.value_preset_name_text_input = name,
+            Msg::SavePreset => {
+                let name = self.value_preset_name_text_input.clone();
+                if is_valid_preset_name(&self.state.presets, &name, self.selected_preset.as_deref())
+                {
+                    self.state.presets.insert(name.clone(), self.search_form());
+                    self.selected_preset = Some(name);
+                    self.state.save_presets()
+                }
+            }
+            Msg::RenamePreset => {
+                if let Some(old_name) = &self.selected_preset {
+                    let name = self.value_preset_name_text_input.clone();
+                    if rename_preset(&mut self.state.presets, old_name, &name) {
+                        self.selected_preset = Some(name);
+                        self.state.save_presets()
+                    }
+                }
+            }
+            Msg::DeletePreset => {
+                if let Some(name) = self.selected_preset.take() {
+                    self.state.presets.remove(&name);
+                    self.value_preset_name_text_input.clear();
+                    self.state.save_presets()
+                }
             }
-            Msg::SliderChanged(index, value) => self.wish_fields[index].value_slider = value,
             Msg::Search => {
-                let wishes: Vec<(Skill, u8)> = self
-                    .wish_fields
-                    .iter()
-                    .map(|wish| (wish.selected.0, wish.value_slider))
-                    .collect();
-                self.builds = pre_selection_then_brute_force_search(
-                    &wishes,
-                    AllArmorSlices {
-                        helmets: &self.helmets,
-                        chests: &self.chests,
-                        arms: &self.arms,
-                        waists: &self.waists,
-                        legs: &self.legs,
-                        talismans: &self.talismans,
-                    },
-                    self.selected_gender,
-                    [
-                        self.states_values_slider_weapon_slot[0],
-                        self.states_values_slider_weapon_slot[1],
-                        self.states_values_slider_weapon_slot[2],
-                    ],
-                );
+                // only one search at a time
+                self.cancel_search();
+
+                let job = Arc::new(self.state.start_search(&self.search_form()));
+                self.save_session();
+
+                self.last_search_id += 1;
+                let id = self.last_search_id;
+                self.running_search = Some(RunningSearch {
+                    id,
+                    job: job.clone(),
+                    done_steps: 0,
+                });
+
+                return Command::perform(run_search_step(job, 0), move |builds| {
+                    Msg::SearchStepDone(id, 0, builds)
+                });
             }
-            Msg::ArmorDesc(option) => self.armor_desc = option,
-            Msg::FilterChanged(text) => {
-                self.value_filter_text_input = text;
-                self.filtered_wish_choices = self
+            Msg::SearchStepDone(id, step, builds) => {
+                let search = match &mut self.running_search {
+                    // results of an aborted search are ignored
+                    Some(search) if search.id == id => search,
+                    _ => return Command::none(),
+                };
+                match builds {
+                    Some(builds) => self.state.add_builds(builds),
+                    None => {
+                        self.running_search = None;
+                        return Command::none();
+                    }
+                }
+                search.done_steps = step + 1;
+                if search.done_steps < search.job.step_count() {
+                    let job = search.job.clone();
+                    let next_step = step + 1;
+                    return Command::perform(run_search_step(job, next_step), move |builds| {
+                        Msg::SearchStepDone(id, next_step, builds)
+                    });
+                }
+                self.running_search = None;
+                if self.state.profile.restore_results {
+                    self.save_session()
+                }
+            }
+            Msg::CancelSearch => self.cancel_search(),
+            Msg::FindAdditionalSkills => {
+                self.cancel_additional_skills();
+
+                let job = Arc::new(self.state.search_job(&self.search_form()));
+                let query = job.query();
+                // the avoided skills are not wanted, even at a higher level
+                let candidates: Vec<Skill> = self
                     .sorted_wish_choices
                     .iter()
-                    .copied()
-                    .filter(|skill| {
-                        skill
-                            .to_string()
-                            .to_lowercase()
-                            .contains(&self.value_filter_text_input.to_lowercase())
-                    })
+                    .map(|skill| skill.0)
+                    .filter(|skill| !query.is_avoided(*skill))
                     .collect();
+
+                self.last_search_id += 1;
+                let id = self.last_search_id;
+                self.additional_skills.clear();
+                self.page = Page::AdditionalSkills;
+
+                // every skill can be avoided, the page stays empty
+                let first_candidate = match candidates.first() {
+                    Some(skill) => *skill,
+                    None => return Command::none(),
+                };
+                self.running_additional_skills = Some(RunningAdditionalSkills {
+                    id,
+                    job: job.clone(),
+                    candidates,
+                    done_steps: 0,
+                });
+
+                return Command::perform(
+                    find_max_additional_level(job, first_candidate),
+                    move |level| Msg::AdditionalSkillStepDone(id, 0, level),
+                );
+            }
+            Msg::AdditionalSkillStepDone(id, index, level) => {
+                let search = match &mut self.running_additional_skills {
+                    Some(search) if search.id == id => search,
+                    _ => return Command::none(),
+                };
+                let level = match level {
+                    Some(level) => level,
+                    None => {
+                        self.running_additional_skills = None;
+                        return Command::none();
+                    }
+                };
+                let skill = search.candidates[index];
+                if level > search.job.query().wished_level(skill) {
+                    self.additional_skills.push((skill, level));
+                }
+                search.done_steps = index + 1;
+                if let Some(&next_candidate) = search.candidates.get(index + 1) {
+                    let job = search.job.clone();
+                    return Command::perform(
+                        find_max_additional_level(job, next_candidate),
+                        move |level| Msg::AdditionalSkillStepDone(id, index + 1, level),
+                    );
+                }
+                self.running_additional_skills = None;
+            }
+            Msg::CancelAdditionalSkills => self.cancel_additional_skills(),
+            Msg::Diagnose => {
+                self.cancel_diagnosis();
+
+                let job = Arc::new(self.state.search_job(&self.search_form()));
+
+                self.last_search_id += 1;
+                let id = self.last_search_id;
+                self.diagnosis = None;
+                self.running_diagnosis = Some((id, job.clone()));
+                self.page = Page::Diagnosis;
+
+                return Command::perform(run_diagnosis(job), move |diagnosis| {
+                    Msg::DiagnosisDone(id, diagnosis)
+                });
+            }
+            Msg::DiagnosisDone(id, diagnosis) => {
+                if matches!(self.running_diagnosis, Some((running_id, _)) if running_id == id) {
+                    self.running_diagnosis = None;
+                    self.diagnosis = diagnosis;
+                }
+            }
+            Msg::CancelDiagnosis => self.cancel_diagnosis(),
+            Msg::MaxWishLevelDone(id, index, level) => {
+                let job = match &self.running_max_levels {
+                    Some((running_id, job)) if *running_id == id => job.clone(),
+                    _ => return Command::none(),
+                };
+                if level.is_none() {
+                    self.running_max_levels = None;
+                    return Command::none();
+                }
+                match self.wish_max_levels.get_mut(index) {
+                    Some(max_level) => *max_level = level,
+                    None => {
+                        self.running_max_levels = None;
+                        return Command::none();
+                    }
+                }
+                let next_index = index + 1;
+                if next_index < self.wish_max_levels.len() {
+                    return Command::perform(find_max_wish_level(job, next_index), move |level| {
+                        Msg::MaxWishLevelDone(id, next_index, level)
+                    });
+                }
+                self.running_max_levels = None;
+            }
+            Msg::SortChanged(sort) => self.state.change_sort(sort),
+            Msg::ArmorDesc(slot, option) => {
+                self.armor_desc = option;
+                self.armor_desc_slot = Some(slot)
+            }
+            Msg::LockArmor(slot, armor) => {
+                self.state.locked_armors[slot.index()] = Some(armor);
+                return self.form_changed();
+            }
+            Msg::UnlockArmor(slot) => {
+                self.state.locked_armors[slot.index()] = None;
+                return self.form_changed();
+            }
+            Msg::ExcludeArmor(name) => {
+                self.state.blacklist.exclude_armor(name);
+                self.state.save_blacklist()
+            }
+            Msg::ExcludeFamily(family) => {
+                self.state.blacklist.exclude_family(family);
+                self.state.save_blacklist()
+            }
+            Msg::RemoveExcludedArmor(name) => {
+                self.state
+                    .blacklist
+                    .armors
+                    .retain(|excluded| excluded != &name);
+                self.state.save_blacklist()
+            }
+            Msg::RemoveExcludedFamily(family) => {
+                self.state
+                    .blacklist
+                    .families
+                    .retain(|excluded| excluded != &family);
+                self.state.save_blacklist()
+            }
+            Msg::JewelAmountChanged(skill, amount) => {
+                if amount == 0 {
+                    self.state.jewel_inventory.remove(&skill);
+                } else {
+                    self.state.jewel_inventory.insert(skill, amount);
+                }
+            }
+            Msg::SaveJewels => {
+                self.state.save_jewel_inventory();
+                return self.compute_max_levels();
+            }
+            Msg::DiscardJewels => {
+                self.state.reload_jewel_inventory();
+                return self.compute_max_levels();
+            }
+            Msg::ToggleIgnoreJewelInventory(ignore) => {
+                self.state.set_ignore_jewel_inventory(ignore);
+                return self.compute_max_levels();
+            }
+            Msg::ToggleRestoreResults(restore) => {
+                self.state.set_restore_results(restore);
+                self.save_session()
+            }
+            Msg::FilterChanged(text) => self.filter_wish_choices(text),
+            Msg::GenderChanged(gender) => {
+                self.selected_gender = gender;
+                return self.form_changed();
             }
-            Msg::GenderChanged(gender) => self.selected_gender = gender,
             Msg::WeaponSlotChanged(index, value) => {
-                self.states_values_slider_weapon_slot[index] = value
+                self.states_values_slider_weapon_slot[index] = value;
+                return self.form_changed();
+            }
+            Msg::FreeSlotChanged(index, value) => {
+                self.states_values_slider_free_slot[index] = value;
+                return self.form_changed();
+            }
+            Msg::MinDefenseChanged(text) => {
+                // an empty field means no minimum
+                if text.is_empty() || text.parse::<u16>().is_ok() {
+                    self.value_min_defense_text_input = text;
+                    return self.form_changed();
+                }
+            }
+            Msg::MinResistanceChanged(index, text) => {
+                if text.is_empty() || text == "-" || text.parse::<i16>().is_ok() {
+                    self.values_min_resistance_text_input[index] = text;
+                    return self.form_changed();
+                }
             }
             Msg::SelectTalisman(index) => self.selected_talisman = index,
             Msg::EditTalisman => {
                 self.is_editing = true;
-                let talisman = &self.talismans[self.selected_talisman.unwrap()];
                 // We provide the actual talisman's data to the edit form
-                self.value_edit_text_input = talisman.name.clone();
+                let draft = TalismanDraft::from_talisman(
+                    &self.state.talismans[self.selected_talisman.unwrap()],
+                );
+                self.value_edit_text_input = draft.name;
 
-                for &(skill, amount) in talisman.skills.iter() {
+                for (skill, amount) in draft.skills {
                     self.edit_wish_fields.push(WishField {
                         selected: LocalizedSkill(skill),
                         value_slider: amount,
+                        ..Default::default()
                     })
                 }
 
-                for (slot, slider_value) in talisman
-                    .slots
-                    .iter()
-                    .zip(self.states_values_slider_talisman_slot.iter_mut())
-                {
-                    *slider_value = *slot;
-                }
+                self.states_values_slider_talisman_slot = draft.slots;
             }
             Msg::SaveEdition => {
                 self.is_editing = false;
-                let talisman = &mut self.talismans[self.selected_talisman.unwrap()];
-                talisman.name = self.value_edit_text_input.clone();
-                talisman.skills = self
-                    .edit_wish_fields
-                    .iter()
-                    .map(|field| (field.selected.0, field.value_slider))
-                    .collect();
-                talisman.slots = self
-                    .states_values_slider_talisman_slot
-                    .iter()
-                    .filter(|slot| **slot > 0)
-                    .copied()
-                    .collect();
+                let draft = TalismanDraft {
+                    name: self.value_edit_text_input.clone(),
+                    skills: self
+                        .edit_wish_fields
+                        .iter()
+                        .map(|field| (field.selected.0, field.value_slider))
+                        .collect(),
+                    slots: self.states_values_slider_talisman_slot,
+                };
+                draft.apply(&mut self.state.talismans[self.selected_talisman.unwrap()]);
                 self.clear_talisman_editor()
             }
             Msg::CancelEdition => {
@@ -522,89 +820,47 @@ impl pure::Application for MainApp {
             }
             Msg::RemoveTalisman => {
                 let index = self.selected_talisman.unwrap();
-                self.talismans.remove(index);
+                self.state.talismans.remove(index);
                 self.clear_talisman_editor();
                 self.is_editing = false;
                 self.selected_talisman = None;
             }
-            Msg::AddTalisman => {
-                self.talismans.push(Armor {
-                    name: "New talisman".to_string(),
-                    skills: vec![(Skill::Botanist, 1)],
-                    ..Default::default()
-                });
-            }
-            Msg::SaveTalismans => match save_talismans_to_file(&self.talismans, TALISMANS_PATH) {
-                Ok(path) => println!("Talismans saved to {}", path),
-                Err(err) => println!("Unable to save the talismans: {}", err),
-            },
+            Msg::AddTalisman => self.state.add_talisman(),
+            Msg::SaveTalismans => self.state.save_talismans(),
             Msg::DiscardTalismans => {
                 self.selected_talisman = None;
-                let talismans = match get_talismans(TALISMANS_PATH) {
-                    Ok(talismans) => {
-                        println!("Talisman file succesfully loaded.");
-                        talismans
-                    }
-                    Err(err) => {
-                        println!(
-                            "Can't read the talisman file: {}\nEmpty talisman list loaded.",
-                            err
-                        );
-                        vec![]
-                    }
-                };
-
-                self.talismans = talismans;
+                self.state.reload_talismans();
             }
             Msg::ChangePage(page) => self.page = page,
             Msg::LocaleChanged(new_locale) => {
-                self.profile.insert("lang".to_string(), new_locale.clone());
-                *super::LOCALE.lock().unwrap() = self.locales.get(&new_locale).cloned();
-                // after the unwrap(), if there is a mystical problem with the mutex
-                // it is better to not save the locale
-                self.save_profile();
+                self.state.change_locale(new_locale);
                 self.sorted_wish_choices
                     .sort_unstable_by(|a, b| natural_lexical_cmp(&a.to_string(), &b.to_string()));
-
-                self.filtered_wish_choices = self
-                    .sorted_wish_choices
-                    .iter()
-                    .copied()
-                    .filter(|skill| {
-                        skill
-                            .to_string()
-                            .to_ascii_lowercase()
-                            .contains(&self.value_filter_text_input.to_ascii_lowercase())
-                    })
-                    .collect();
-
-                self.selected_locale = new_locale;
+                self.filter_wish_choices(self.value_filter_text_input.clone());
             }
             Msg::ToggleTheme => {
                 self.theme = match self.theme {
-                    style_iced::Theme::Dark => {
-                        self.profile
-                            .insert("theme".to_string(), "light".to_string());
-                        style_iced::Theme::Light
-                    }
-                    _ => {
-                        self.profile.insert("theme".to_string(), "dark".to_string());
-                        style_iced::Theme::Dark
-                    }
+                    style_iced::Theme::Dark => style_iced::Theme::Light,
+                    _ => style_iced::Theme::Dark,
                 };
-                self.save_profile()
+                self.state.profile.theme = self.theme;
+                self.state.save_profile()
             }
             Msg::UpdateArmors => {
                 self.update_state = UpdateState::Updating;
-                create_locale_and_armors_dir();
-                return Command::perform(download_armors_and_locales(), |no_problem| {
-                    Msg::UpdateDone(no_problem)
-                });
+                self.state.paths.create_dirs();
+                return Command::perform(
+                    download_armors_and_locales(
+                        self.state.paths.armors_dir.clone(),
+                        self.state.paths.locale_dir.clone(),
+                    ),
+                    Msg::UpdateDone,
+                );
             }
             Msg::UpdateDone(no_problem) => {
                 self.update_state = if no_problem {
-                    self.reload_locales();
-                    self.reload_armors();
+                    self.state.reload_locales();
+                    self.state.reload_armors();
 
                     UpdateState::Done
                 } else {
@@ -613,15 +869,19 @@ impl pure::Application for MainApp {
             }
             Msg::DownloadArmors => {
                 self.update_state = UpdateState::Updating;
-                create_locale_and_armors_dir();
-                return Command::perform(download_armors_and_locales(), |no_problem| {
-                    Msg::DownloadDone(no_problem)
-                });
+                self.state.paths.create_dirs();
+                return Command::perform(
+                    download_armors_and_locales(
+                        self.state.paths.armors_dir.clone(),
+                        self.state.paths.locale_dir.clone(),
+                    ),
+                    Msg::DownloadDone,
+                );
             }
             Msg::DownloadDone(no_problem) => {
                 self.update_state = if no_problem {
-                    self.reload_locales();
-                    self.reload_armors();
+                    self.state.reload_locales();
+                    self.state.reload_armors();
 
                     self.page = Page::Lang;
                     UpdateState::Done
@@ -632,47 +892,109 @@ impl pure::Application for MainApp {
             Msg::BuildDetails(index) => {
                 self.value_edit_text_input = "".to_string();
 
-                self.focus_new_build(self.builds[index].clone());
+                self.focus_new_build(self.state.results.builds[index].clone());
 
-                self.details_build_index = index;
                 self.page = Page::Details(false)
             }
-            Msg::SaveBuild(index) => {
-                self.saved_builds.insert(
-                    self.value_edit_text_input.clone(),
-                    self.builds[index].clone(),
-                );
-
-                self.page = Page::Builds;
-                self.save_builds();
+            Msg::SaveBuild => {
+                // the focused build is used because the results may be sorted again
+                // while the details page is shown
+                if let Some(build) = &self.focused_build {
+                    self.state
+                        .save_build(self.value_edit_text_input.clone(), build.clone());
+                    self.page = Page::Builds;
+                }
             }
             Msg::SavedBuildDetails(name) => {
                 self.value_edit_text_input = name.clone();
 
-                self.focus_new_build(self.saved_builds.get(&name).unwrap().clone());
+                self.focus_new_build(self.state.saved_builds.get(&name).unwrap().clone());
 
                 self.details_build_name = name;
                 self.page = Page::Details(true)
             }
             Msg::EditSavedBuild(name) => {
-                let build = self.saved_builds.remove(&name).unwrap();
-                self.saved_builds
-                    .insert(self.value_edit_text_input.clone(), build);
+                self.state
+                    .rename_saved_build(&name, self.value_edit_text_input.clone());
 
                 self.details_build_name = self.value_edit_text_input.clone();
 
                 self.page = Page::Builds;
-                self.save_builds();
             }
             Msg::RemoveSavedBuild(name) => {
-                self.saved_builds.remove(&name);
-                self.save_builds();
+                self.state.remove_saved_build(&name);
+            }
+            Msg::CopyShareCode => {
+                if let Some(build) = &self.focused_build {
+                    return clipboard::write(build_to_share_code(build));
+                }
+            }
+            Msg::ShareCodeChanged(code) => {
+                self.value_share_code_text_input = code;
+                self.share_code_error = None;
+            }
+            Msg::ImportShareCode => {
+                match self
+                    .state
+                    .build_from_share_code(&self.value_share_code_text_input)
+                {
+                    // shown like a found build, to be saved under a new name
+                    Ok(build) => {
+                        self.value_edit_text_input = "".to_string();
+                        self.value_share_code_text_input = "".to_string();
+                        self.focus_new_build(build);
+                        self.page = Page::Details(false)
+                    }
+                    Err(err) => self.share_code_error = Some(err.to_string()),
+                }
+            }
+            Msg::CopyQueryCode => {
+                return clipboard::write(form_to_query_code(&self.search_form()));
+            }
+            Msg::QueryCodeChanged(code) => {
+                self.value_query_code_text_input = code;
+                self.query_code_error = None;
+            }
+            Msg::ImportQueryCode => {
+                let mut form = self.search_form();
+                match apply_query_code(&self.value_query_code_text_input, &mut form) {
+                    Ok(()) => {
+                        self.value_query_code_text_input = "".to_string();
+                        self.apply_search_form(form);
+                        return self.form_changed();
+                    }
+                    Err(err) => self.query_code_error = Some(err.to_string()),
+                }
+            }
+            Msg::CopyExport(format) => {
+                if let Some(build) = &self.focused_build {
+                    // the name field may not be saved yet
+                    let name = Some(self.value_edit_text_input.as_str()).filter(|n| !n.is_empty());
+                    return clipboard::write(export_build(name, build, format));
+                }
+            }
+            Msg::ExportSavedBuilds(format) => self.state.export_saved_builds(format),
+            Msg::CloseRequested => {
+                self.save_session();
+                self.should_exit = true;
             }
         };
         Command::none()
     }
 
-    fn view(&self) -> pure::Element<Msg> {
+    // the window is closed by should_exit, once the session is saved
+    fn subscription(&self) -> Subscription<Msg> {
+        subscription::events_with(|event, _| match event {
+            Event::Window(window::Event::CloseRequested) => Some(Msg::CloseRequested),
+            _ => None,
+        })
+    }
+
+    fn should_exit(&self) -> bool {
+        self.should_exit
+    }
+
+    fn view(&self) -> pure::Element<'_, Msg> {
         let theme = self.theme;
 
         // can't use pure::Element::<Msg>::from()
@@ -685,6 +1007,10 @@ impl pure::Application for MainApp {
             Page::Lang => self.get_lang_page().into(),
             Page::Details(on_save_builds) => self.get_details_page(on_save_builds).into(),
             Page::Builds => self.get_builds_page().into(),
+            Page::Blacklist => self.get_blacklist_page().into(),
+            Page::Jewels => self.get_jewels_page().into(),
+            Page::AdditionalSkills => self.get_additional_skills_page().into(),
+            Page::Diagnosis => self.get_diagnosis_page().into(),
         })
         .width(Length::Fill)
         .height(Length::Fill)
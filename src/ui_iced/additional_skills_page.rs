@@ -0,0 +1,58 @@
+use super::{
+    common_elements::{skill_and_amount, BUTTON_SPACING, COLUMN_SPACING, SCROLL_PADDING},
+    MainApp, Msg, Page,
+};
+use crate::style_iced;
+use iced::{pure, Alignment, Length, Space, Text};
+use rab::locale::InterfaceSymbol;
+
+pub trait AdditionalSkillsPage {
+    fn get_additional_skills_page(&self) -> pure::widget::Column<'_, Msg>;
+}
+
+impl AdditionalSkillsPage for MainApp {
+    fn get_additional_skills_page(&self) -> pure::widget::Column<'_, Msg> {
+        let mut skills_scroll = pure::column()
+            .align_items(Alignment::Center)
+            .padding(SCROLL_PADDING)
+            .spacing(5);
+
+        if self.additional_skills.is_empty() && self.running_additional_skills.is_none() {
+            skills_scroll = skills_scroll.push(Text::new(InterfaceSymbol::NoResult));
+        }
+
+        // the level is the total level with the wishes
+        for (skill, level) in self.additional_skills.iter() {
+            skills_scroll = skills_scroll.push(skill_and_amount(skill, *level));
+        }
+
+        let mut column = pure::column()
+            .spacing(COLUMN_SPACING)
+            .padding(5)
+            .align_items(Alignment::Center)
+            .push(Text::new(InterfaceSymbol::AdditionalSkills));
+
+        if let Some(search) = &self.running_additional_skills {
+            column = column.push(
+                pure::row()
+                    .spacing(BUTTON_SPACING)
+                    .align_items(Alignment::Center)
+                    .push(pure::progress_bar(0.0..=1.0, search.progress()))
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::Cancel))
+                            .style(style_iced::Button::Cancel)
+                            .on_press(Msg::CancelAdditionalSkills),
+                    ),
+            );
+        }
+
+        column
+            .push(pure::scrollable(skills_scroll).height(Length::Fill))
+            .push(
+                pure::row().push(Space::with_width(Length::Fill)).push(
+                    pure::button(Text::new(InterfaceSymbol::Back))
+                        .on_press(Msg::ChangePage(Page::Main)),
+                ),
+            )
+    }
+}
@@ -0,0 +1,105 @@
+use super::{
+    common_elements::{BUTTON_SPACING, COLUMN_SPACING, SCROLL_PADDING},
+    MainApp, Msg, Page,
+};
+use crate::style_iced;
+use iced::{pure, Alignment, Length, Space, Text};
+use lexical_sort::natural_lexical_cmp;
+use rab::{
+    blacklist::armor_family,
+    locale::{InterfaceSymbol, LocalizedArmor},
+};
+
+pub trait BlacklistPage {
+    fn get_blacklist_page(&self) -> pure::widget::Column<'_, Msg>;
+}
+
+impl BlacklistPage for MainApp {
+    fn get_blacklist_page(&self) -> pure::widget::Column<'_, Msg> {
+        let mut pieces_scroll = pure::column()
+            .align_items(Alignment::Center)
+            .padding(SCROLL_PADDING)
+            .spacing(10);
+
+        if self.state.blacklist.armors.is_empty() {
+            pieces_scroll = pieces_scroll.push(Text::new(InterfaceSymbol::NoResult));
+        }
+
+        for name in self.state.blacklist.armors.iter() {
+            let localized_name = match self.state.all_armors().find(|armor| &armor.name == name) {
+                Some(armor) => LocalizedArmor(armor).to_string(),
+                None => name.clone(),
+            };
+            pieces_scroll = pieces_scroll.push(excluded_row(
+                localized_name,
+                Msg::RemoveExcludedArmor(name.clone()),
+            ));
+        }
+
+        let mut families: Vec<String> = self
+            .state
+            .all_armors()
+            .map(|armor| armor_family(&armor.name).to_string())
+            .filter(|family| !self.state.blacklist.families.contains(family))
+            .collect();
+        families.sort_unstable_by(|a, b| natural_lexical_cmp(a, b));
+        families.dedup();
+
+        let mut families_scroll = pure::column()
+            .align_items(Alignment::Center)
+            .padding(SCROLL_PADDING)
+            .spacing(10)
+            .push(
+                pure::pick_list(families, None, Msg::ExcludeFamily)
+                    .placeholder(InterfaceSymbol::ExcludeFamily.to_string()),
+            );
+
+        for family in self.state.blacklist.families.iter() {
+            families_scroll = families_scroll.push(excluded_row(
+                family.clone(),
+                Msg::RemoveExcludedFamily(family.clone()),
+            ));
+        }
+
+        pure::column()
+            .spacing(COLUMN_SPACING)
+            .padding(5)
+            .push(
+                pure::row()
+                    .spacing(BUTTON_SPACING)
+                    .height(Length::Fill)
+                    .push(
+                        pure::column()
+                            .align_items(Alignment::Center)
+                            .width(Length::Fill)
+                            .push(Text::new(InterfaceSymbol::ExcludedPieces))
+                            .push(pure::scrollable(pieces_scroll)),
+                    )
+                    .push(
+                        pure::column()
+                            .align_items(Alignment::Center)
+                            .width(Length::Fill)
+                            .push(Text::new(InterfaceSymbol::ExcludedFamilies))
+                            .push(pure::scrollable(families_scroll)),
+                    ),
+            )
+            .push(
+                pure::row().push(Space::with_width(Length::Fill)).push(
+                    pure::button(Text::new(InterfaceSymbol::Back))
+                        .on_press(Msg::ChangePage(Page::Main)),
+                ),
+            )
+    }
+}
+
+fn excluded_row<'a>(name: String, on_remove: Msg) -> pure::widget::Row<'a, Msg> {
+    pure::row()
+        .spacing(10)
+        .align_items(Alignment::Center)
+        .push(Text::new(name).width(Length::Units(200)))
+        .push(
+            pure::button(Text::new(InterfaceSymbol::Remove))
+                .style(style_iced::Button::Remove)
+                .on_press(on_remove),
+        )
+}
@@ -5,12 +5,13 @@ use super::{
     },
     MainApp, Msg, Page,
 };
-use crate::{locale::InterfaceSymbol, style_iced};
+use crate::style_iced;
 use iced::{
     alignment, pure,
     widget::svg::{Handle, Svg},
     Alignment, Length, Rule, Space, Text,
 };
+use rab::{build_stats::ArmorSlot, export::ExportFormat, locale::InterfaceSymbol};
 
 pub trait BuildsPage {
     fn get_builds_page(&self) -> pure::widget::Container<'_, Msg>;
@@ -18,7 +19,7 @@ pub trait BuildsPage {
 
 impl BuildsPage for MainApp {
     fn get_builds_page(&'_ self) -> pure::widget::Container<'_, Msg> {
-        let builds = &self.saved_builds;
+        let builds = &self.state.saved_builds;
         let mut builds_scrolls = pure::column()
             .align_items(Alignment::Center)
             .spacing(10)
@@ -51,12 +52,12 @@ impl BuildsPage for MainApp {
                         .width(Length::Units(100)),
                     )
                     .push(details_button)
-                    .push(build_part_to_button(&build.helmet))
-                    .push(build_part_to_button(&build.chest))
-                    .push(build_part_to_button(&build.arm))
-                    .push(build_part_to_button(&build.waist))
-                    .push(build_part_to_button(&build.leg))
-                    .push(build_part_to_button(&build.talisman));
+                    .push(build_part_to_button(ArmorSlot::Helmet, &build.helmet))
+                    .push(build_part_to_button(ArmorSlot::Chest, &build.chest))
+                    .push(build_part_to_button(ArmorSlot::Arm, &build.arm))
+                    .push(build_part_to_button(ArmorSlot::Waist, &build.waist))
+                    .push(build_part_to_button(ArmorSlot::Leg, &build.leg))
+                    .push(build_part_to_button(ArmorSlot::Talisman, &build.talisman));
                 builds_scrolls = builds_scrolls.push(row_build);
                 if key < size - 1 {
                     builds_scrolls = builds_scrolls.push(Rule::horizontal(1))
@@ -65,11 +66,7 @@ impl BuildsPage for MainApp {
         }
 
         // to center the titles
-        let space_width = if SCROLL_PADDING < BUTTON_SPACING {
-            0
-        } else {
-            SCROLL_PADDING - BUTTON_SPACING
-        };
+        let space_width = SCROLL_PADDING.saturating_sub(BUTTON_SPACING);
 
         let mut col_titles = pure::row()
             .spacing(BUTTON_SPACING)
@@ -96,10 +93,40 @@ impl BuildsPage for MainApp {
                 .push(col_titles.push(Space::with_width(Length::Units(space_width))))
                 .push(pure::scrollable(builds_scrolls.width(Length::Fill)).height(Length::Fill))
                 .push(
-                    pure::row().push(Space::with_width(Length::Fill)).push(
-                        pure::button(Text::new(InterfaceSymbol::Back))
-                            .on_press(Msg::ChangePage(Page::Main)),
-                    ),
+                    pure::row()
+                        .spacing(BUTTON_SPACING)
+                        .align_items(Alignment::Center)
+                        .push(
+                            pure::text_input(
+                                &InterfaceSymbol::ShareCode.to_string(),
+                                &self.value_share_code_text_input,
+                                Msg::ShareCodeChanged,
+                            )
+                            .on_submit(Msg::ImportShareCode)
+                            .padding(5)
+                            .width(Length::Units(300)),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::ImportFromCode))
+                                .style(style_iced::Button::Add)
+                                .on_press(Msg::ImportShareCode),
+                        )
+                        .push(
+                            Text::new(self.share_code_error.clone().unwrap_or_default())
+                                .width(Length::Fill),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::ExportAsMarkdown))
+                                .on_press(Msg::ExportSavedBuilds(ExportFormat::Markdown)),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::ExportAsText))
+                                .on_press(Msg::ExportSavedBuilds(ExportFormat::Text)),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::Back))
+                                .on_press(Msg::ChangePage(Page::Main)),
+                        ),
                 ),
         )
         .padding(5)
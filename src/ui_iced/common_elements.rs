@@ -1,19 +1,21 @@
 use super::{Msg, UpdateState, WishField};
-use crate::locale::InterfaceSymbol;
-use crate::{
-    locale::{LocalizedArmor, LocalizedSkill},
-    style_iced,
-};
+use crate::style_iced;
 use iced::{
     alignment, pure,
     widget::svg::{Handle, Svg},
     Alignment, Length, Rule, Space, Text,
 };
+use rab::locale::InterfaceSymbol;
+use rab::{
+    build_stats::{optional_wishes_met, place_jewels, ArmorSlot},
+    export::slot_text,
+    locale::{LocalizedArmor, LocalizedSkill},
+    search_form::WishKind,
+};
 use rab_core::{
     armor_and_skills::{Armor, Skill},
     build_search::{Build, Jewels},
 };
-use std::cmp::Reverse;
 
 pub(super) const HEIGHT_BIG_BUTTON: u16 = 60;
 pub(super) const BUTTON_SPACING: u16 = 10;
@@ -39,7 +41,11 @@ pub(super) const WAIST_ICON: &[u8] = include_bytes!("icons/waist.svg");
 pub(super) const LEG_ICON: &[u8] = include_bytes!("icons/leg.svg");
 pub(super) const TALISMAN_ICON: &[u8] = include_bytes!("icons/talisman.svg");
 
-pub(super) fn get_column_builds_found(builds: &[Build]) -> pure::widget::Column<Msg> {
+// optional wishes are (skill, level, weight)
+pub(super) fn get_column_builds_found<'a>(
+    builds: &'a [Build],
+    optional_wishes: &[(Skill, u8, u8)],
+) -> pure::widget::Column<'a, Msg> {
     let mut builds_column = pure::column()
         .align_items(Alignment::Center)
         .spacing(10)
@@ -65,13 +71,29 @@ pub(super) fn get_column_builds_found(builds: &[Build]) -> pure::widget::Column<
                 .align_items(Alignment::Center)
                 .spacing(BUTTON_SPACING)
                 .push(details_button.width(Length::Units(DETAIL_BUTTON_SIZE)))
-                .push(build_part_to_button(&build.helmet))
-                .push(build_part_to_button(&build.chest))
-                .push(build_part_to_button(&build.arm))
-                .push(build_part_to_button(&build.waist))
-                .push(build_part_to_button(&build.leg))
-                .push(build_part_to_button(&build.talisman));
+                .push(build_part_to_button(ArmorSlot::Helmet, &build.helmet))
+                .push(build_part_to_button(ArmorSlot::Chest, &build.chest))
+                .push(build_part_to_button(ArmorSlot::Arm, &build.arm))
+                .push(build_part_to_button(ArmorSlot::Waist, &build.waist))
+                .push(build_part_to_button(ArmorSlot::Leg, &build.leg))
+                .push(build_part_to_button(ArmorSlot::Talisman, &build.talisman));
             builds_column = builds_column.push(row_build);
+            if !optional_wishes.is_empty() {
+                let met = optional_wishes_met(build, optional_wishes);
+                builds_column = builds_column.push(Text::new(if met.is_empty() {
+                    InterfaceSymbol::NoOptionalWishMet.to_string()
+                } else {
+                    InterfaceSymbol::TemplateOptionalWishesMet
+                        .to_string()
+                        .replace(
+                            "{skills}",
+                            &met.into_iter()
+                                .map(|skill| LocalizedSkill(skill).to_string())
+                                .collect::<Vec<String>>()
+                                .join(", "),
+                        )
+                }));
+            }
             if key < size - 1 {
                 builds_column = builds_column.push(Rule::horizontal(1))
             }
@@ -79,11 +101,7 @@ pub(super) fn get_column_builds_found(builds: &[Build]) -> pure::widget::Column<
     }
 
     // to center the titles
-    let space_width = if SCROLL_PADDING < BUTTON_SPACING {
-        0
-    } else {
-        SCROLL_PADDING - BUTTON_SPACING
-    };
+    let space_width = SCROLL_PADDING.saturating_sub(BUTTON_SPACING);
 
     let mut col_titles = pure::row()
         .spacing(BUTTON_SPACING)
@@ -110,10 +128,12 @@ pub(super) fn get_column_builds_found(builds: &[Build]) -> pure::widget::Column<
         .push(pure::scrollable(builds_column.width(Length::Fill)))
 }
 
+// max_level is the highest level reachable with the other wishes, if known
 pub(super) fn get_wishfield_row<'a>(
     wish_field: &'a WishField,
     skill_list: &'a [LocalizedSkill],
     disable_remove_button: bool,
+    max_level: Option<u8>,
     on_remove: Msg,
     on_skill_selected: impl Fn(LocalizedSkill) -> Msg + 'static,
     on_slider_changed: impl Fn(u8) -> Msg + 'static,
@@ -125,19 +145,40 @@ pub(super) fn get_wishfield_row<'a>(
     if !disable_remove_button {
         remove_button = remove_button.on_press(on_remove);
     }
+    // an avoided skill can be forbidden entirely
+    let lowest_level = if wish_field.kind == WishKind::Avoid {
+        0
+    } else {
+        1
+    };
     let slider = pure::slider(
-        1..=wish_field.selected.get_limit(),
+        lowest_level..=wish_field.selected.get_limit(),
         wish_field.value_slider,
         on_slider_changed,
     )
     .width(Length::Units(100));
     let text = Text::new(format!("{}", wish_field.value_slider));
-    pure::row()
+    let mut row = pure::row()
         .spacing(10)
         .push(pick_list)
         .push(slider)
-        .push(text)
-        .push(remove_button)
+        .push(text);
+    if let Some(max_level) = max_level {
+        row = row.push(
+            pure::container(Text::new(
+                InterfaceSymbol::TemplateMaxLevel
+                    .to_string()
+                    .replace("{level}", &max_level.to_string()),
+            ))
+            .padding(2)
+            .style(if max_level < wish_field.value_slider {
+                style_iced::Container::Warning
+            } else {
+                style_iced::Container::Defense
+            }),
+        );
+    }
+    row.push(remove_button)
 }
 
 pub(super) fn get_skill_filter<'a>(value: &str) -> pure::widget::TextInput<'a, Msg> {
@@ -150,8 +191,9 @@ pub(super) fn get_skill_filter<'a>(value: &str) -> pure::widget::TextInput<'a, M
 }
 
 pub(super) fn build_part_to_button<'a>(
+    slot: ArmorSlot,
     build_part: &Option<(Armor, Jewels)>,
-) -> pure::widget::Button<'a, Msg> {
+) -> pure::widget::Column<'a, Msg> {
     let button = pure::button(
         pure::container(Text::new(if let Some((armor, _)) = build_part {
             LocalizedArmor(armor).to_string()
@@ -166,10 +208,24 @@ pub(super) fn build_part_to_button<'a>(
     .style(style_iced::Button::Result)
     .width(Length::Fill)
     .height(Length::Units(HEIGHT_BIG_BUTTON));
-    if build_part.is_none() {
-        button
-    } else {
-        button.on_press(Msg::ArmorDesc(build_part.clone()))
+    let column = pure::column()
+        .width(Length::Fill)
+        .spacing(2)
+        .align_items(Alignment::Center);
+    match build_part {
+        None => column.push(button),
+        // talismans are managed on their own page
+        Some(_) if slot == ArmorSlot::Talisman => {
+            column.push(button.on_press(Msg::ArmorDesc(slot, build_part.clone())))
+        }
+        Some((armor, _)) => column
+            .push(button.on_press(Msg::ArmorDesc(slot, build_part.clone())))
+            .push(
+                pure::button(Text::new(InterfaceSymbol::Exclude).size(14))
+                    .padding(2)
+                    .style(style_iced::Button::Remove)
+                    .on_press(Msg::ExcludeArmor(armor.name.clone())),
+            ),
     }
 }
 
@@ -185,7 +241,7 @@ pub(super) fn update_button<'a>(
                     UpdateState::Initial => DOWNLOAD_ICON,
                     UpdateState::Done => CHECK_ICON,
                     UpdateState::Updating => SYNC_ICON,
-                    UpdateState::Problem => CROSS_ICON,
+                    UpdateState::Problem | UpdateState::Offline => CROSS_ICON,
                 }))
                 .width(ICON_LENGTH),
             )
@@ -195,18 +251,23 @@ pub(super) fn update_button<'a>(
                     UpdateState::Done => InterfaceSymbol::Updated,
                     UpdateState::Updating => InterfaceSymbol::Updating,
                     UpdateState::Problem => InterfaceSymbol::ProblemCheckConsole,
+                    UpdateState::Offline => InterfaceSymbol::OfflineMode,
                 })
                 .height(Length::Fill)
                 .vertical_alignment(alignment::Vertical::Center),
             ),
     );
     match update_state {
-        UpdateState::Updating | UpdateState::Done => b,
+        UpdateState::Updating | UpdateState::Done | UpdateState::Offline => b,
         _ => b.on_press(msg),
     }
 }
 
-pub(super) fn armor_desc_to_element(armor: &Option<(Armor, Jewels)>) -> pure::widget::Column<Msg> {
+// The jewels of the missing skills are highlighted
+pub(super) fn armor_desc_to_element<'a>(
+    armor: &'a Option<(Armor, Jewels)>,
+    missing_jewels: &[Skill],
+) -> pure::widget::Column<'a, Msg> {
     if let Some((armor, jewel_skills)) = armor {
         let mut col_armor_stats = pure::column()
             .align_items(Alignment::Center)
@@ -269,44 +330,14 @@ pub(super) fn armor_desc_to_element(armor: &Option<(Armor, Jewels)>) -> pure::wi
             col_armor_stats = col_armor_stats.push(Space::with_height(Length::Units(10)));
         }
 
-        let mut slots = armor.slots.clone();
-        slots.sort_unstable();
-
-        let mut couple_slot_jewel = Vec::with_capacity(3);
-
-        let mut jewel_skills: Vec<Skill> = jewel_skills.iter().copied().flatten().collect();
-        // reverse order
-        jewel_skills.sort_unstable_by_key(|a| Reverse(a.get_jewel_size()));
-
-        // to be sure that the jewel will be on the most little slot possible
-        let mut to_remove = None;
-        'slot_loop: for slot in slots {
-            if let Some(index) = to_remove {
-                jewel_skills.swap_remove(index);
-                to_remove = None;
-            }
-            for (index, skill) in jewel_skills.iter().enumerate() {
-                if skill.get_jewel_size().unwrap() <= slot {
-                    couple_slot_jewel.push((slot, Some(*skill)));
-                    to_remove = Some(index);
-                    continue 'slot_loop;
-                }
-            }
-            couple_slot_jewel.push((slot, None));
-        }
-
-        for (slot, skill) in couple_slot_jewel {
+        for (slot, skill) in place_jewels(&armor.slots, jewel_skills) {
             col_armor_stats = col_armor_stats.push(if let Some(skill) = skill {
-                jewel_on_slot(&skill, slot)
+                jewel_on_slot(&skill, slot, missing_jewels.contains(&skill))
             } else {
-                pure::container(Text::new(
-                    InterfaceSymbol::TemplateFreeSlot
-                        .to_string()
-                        .replace("{size}", &slot.to_string()),
-                ))
-                .width(Length::Units(170))
-                .center_x()
-                .style(style_iced::Container::Ice)
+                pure::container(Text::new(slot_text(slot, None)))
+                    .width(Length::Units(170))
+                    .center_x()
+                    .style(style_iced::Container::Ice)
             });
         }
 
@@ -316,16 +347,37 @@ pub(super) fn armor_desc_to_element(armor: &Option<(Armor, Jewels)>) -> pure::wi
     }
 }
 
-pub(super) fn jewel_on_slot<'a>(skill: &Skill, slot: u8) -> pure::widget::Container<'a, Msg> {
-    pure::container(Text::new(
-        InterfaceSymbol::TemplateJewelOnSlot
-            .to_string()
-            .replace("{skill}", &LocalizedSkill(*skill).to_string())
-            .replace("{size}", &slot.to_string()),
-    ))
-    .width(Length::Units(170))
-    .center_x()
-    .style(style_iced::Container::Ice)
+// Lock or unlock the piece, depending on the current lock of its slot
+pub(super) fn lock_button<'a>(
+    slot: ArmorSlot,
+    armor: &Armor,
+    locked_armors: &[Option<Armor>; 6],
+) -> pure::widget::Button<'a, Msg> {
+    match &locked_armors[slot.index()] {
+        Some(locked) if locked.name == armor.name => {
+            pure::button(Text::new(InterfaceSymbol::Unlock))
+                .style(style_iced::Button::Remove)
+                .on_press(Msg::UnlockArmor(slot))
+        }
+        _ => pure::button(Text::new(InterfaceSymbol::Lock))
+            .style(style_iced::Button::Edit)
+            .on_press(Msg::LockArmor(slot, armor.clone())),
+    }
+}
+
+pub(super) fn jewel_on_slot<'a>(
+    skill: &Skill,
+    slot: u8,
+    missing: bool,
+) -> pure::widget::Container<'a, Msg> {
+    pure::container(Text::new(slot_text(slot, Some(*skill))))
+        .width(Length::Units(170))
+        .center_x()
+        .style(if missing {
+            style_iced::Container::Warning
+        } else {
+            style_iced::Container::Ice
+        })
 }
 
 pub(super) const SKILL_AMOUNT_SIZE: u16 = 150;
@@ -1,29 +1,31 @@
 use super::{
     common_elements::{
-        armor_desc_to_element, skill_and_amount, ARM_ICON, BUTTON_SPACING, CHEST_ICON,
+        armor_desc_to_element, lock_button, skill_and_amount, ARM_ICON, BUTTON_SPACING, CHEST_ICON,
         COLUMN_SPACING, HELMET_ICON, ICON_LENGTH, LEG_ICON, SCROLL_PADDING, SKILL_AMOUNT_SIZE,
         TALISMAN_ICON, WAIST_ICON,
     },
     MainApp, Msg, Page,
 };
-use crate::{
-    locale::{InterfaceSymbol, LocalizedSkill},
-    style_iced,
-};
+use crate::style_iced;
 use iced::{
     pure,
     widget::svg::{Handle, Svg},
     Alignment, Length, Space, Text,
 };
+use rab::{
+    build_stats::{build_jewel_counts, build_parts, missing_jewels, ArmorSlot},
+    export::ExportFormat,
+    locale::{InterfaceSymbol, LocalizedSkill},
+};
 
 // need refactoring
 
 pub trait DetailsPage {
-    fn get_details_page(&self, on_save_builds: bool) -> pure::widget::Column<Msg>;
+    fn get_details_page(&self, on_save_builds: bool) -> pure::widget::Column<'_, Msg>;
 }
 
 impl DetailsPage for MainApp {
-    fn get_details_page(&self, on_save_builds: bool) -> pure::widget::Column<Msg> {
+    fn get_details_page(&self, on_save_builds: bool) -> pure::widget::Column<'_, Msg> {
         let mut row = pure::row();
         let mut row_title = pure::row()
             .push(Space::with_width(Length::Units(SCROLL_PADDING)))
@@ -42,7 +44,6 @@ impl DetailsPage for MainApp {
                     .center_x(),
             );
         }
-        let build_index = self.details_build_index;
         let build = self.focused_build.as_ref().unwrap();
 
         let mut col_skills = pure::column().spacing(5);
@@ -53,22 +54,24 @@ impl DetailsPage for MainApp {
 
         row = row.push(col_skills);
 
-        for part in [
-            &build.helmet,
-            &build.chest,
-            &build.arm,
-            &build.waist,
-            &build.leg,
-            &build.talisman,
-        ] {
-            row = row.push(if part.is_none() {
+        let missing = if self.state.profile.ignore_jewel_inventory {
+            vec![]
+        } else {
+            missing_jewels(&build_jewel_counts(build), &self.state.jewel_inventory)
+        };
+
+        for (slot, part) in ArmorSlot::ALL.into_iter().zip(build_parts(build)) {
+            row = row.push(if let Some((armor, _)) = part {
+                armor_desc_to_element(part, &missing)
+                    .push(lock_button(slot, armor, &self.state.locked_armors))
+                    .width(Length::Fill)
+                    .into()
+            } else {
                 pure::Element::<Msg>::from(
                     pure::container(Text::new(InterfaceSymbol::Free))
                         .width(Length::Fill)
                         .center_x(),
                 )
-            } else {
-                armor_desc_to_element(part).width(Length::Fill).into()
             })
         }
 
@@ -80,7 +83,11 @@ impl DetailsPage for MainApp {
             weapon_jewels_row = weapon_jewels_row.push(
                 pure::container(Text::new(LocalizedSkill(*jewel).to_string()))
                     .center_x()
-                    .style(style_iced::Container::Ice)
+                    .style(if missing.contains(jewel) {
+                        style_iced::Container::Warning
+                    } else {
+                        style_iced::Container::Ice
+                    })
                     .width(Length::Units(170)),
             )
         }
@@ -108,8 +115,20 @@ impl DetailsPage for MainApp {
                                 .on_press(if on_save_builds {
                                     Msg::EditSavedBuild(self.details_build_name.clone())
                                 } else {
-                                    Msg::SaveBuild(build_index)
+                                    Msg::SaveBuild
                                 }),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::CopyShareCode))
+                                .on_press(Msg::CopyShareCode),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::CopyAsMarkdown))
+                                .on_press(Msg::CopyExport(ExportFormat::Markdown)),
+                        )
+                        .push(
+                            pure::button(Text::new(InterfaceSymbol::CopyAsText))
+                                .on_press(Msg::CopyExport(ExportFormat::Text)),
                         ),
                 )
                 .width(Length::Fill)
@@ -0,0 +1,95 @@
+use super::{
+    common_elements::{skill_and_amount, BUTTON_SPACING, COLUMN_SPACING, SCROLL_PADDING},
+    MainApp, Msg, Page,
+};
+use crate::style_iced;
+use iced::{pure, Alignment, Length, Space, Text};
+use rab::locale::{InterfaceSymbol, LocalizedSkill};
+
+pub trait DiagnosisPage {
+    fn get_diagnosis_page(&self) -> pure::widget::Column<'_, Msg>;
+}
+
+impl DiagnosisPage for MainApp {
+    fn get_diagnosis_page(&self) -> pure::widget::Column<'_, Msg> {
+        let mut diagnosis_scroll = pure::column()
+            .align_items(Alignment::Center)
+            .padding(SCROLL_PADDING)
+            .spacing(5);
+
+        if self.running_diagnosis.is_some() {
+            diagnosis_scroll = diagnosis_scroll.push(
+                pure::row()
+                    .spacing(BUTTON_SPACING)
+                    .align_items(Alignment::Center)
+                    .push(Text::new(InterfaceSymbol::Diagnosing))
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::Cancel))
+                            .style(style_iced::Button::Cancel)
+                            .on_press(Msg::CancelDiagnosis),
+                    ),
+            );
+        } else if let Some(diagnosis) = &self.diagnosis {
+            diagnosis_scroll = diagnosis_scroll.push(Text::new(InterfaceSymbol::MaxLevelsAlone));
+            for (skill, wished_level, max_level) in diagnosis.max_levels_alone.iter() {
+                diagnosis_scroll = diagnosis_scroll.push(Text::new(
+                    InterfaceSymbol::TemplateLevelAlone
+                        .to_string()
+                        .replace("{skill}", &LocalizedSkill(*skill).to_string())
+                        .replace("{max}", &max_level.to_string())
+                        .replace("{wished}", &wished_level.to_string()),
+                ));
+            }
+
+            diagnosis_scroll = diagnosis_scroll.push(Space::with_height(Length::Units(10)));
+
+            match &diagnosis.minimal_conflict {
+                Some(conflict) => {
+                    diagnosis_scroll =
+                        diagnosis_scroll.push(Text::new(InterfaceSymbol::MinimalConflict));
+                    for (skill, level) in conflict.iter() {
+                        diagnosis_scroll = diagnosis_scroll.push(skill_and_amount(skill, *level));
+                    }
+                }
+                None => {
+                    diagnosis_scroll =
+                        diagnosis_scroll.push(Text::new(InterfaceSymbol::NoWishConflict))
+                }
+            }
+
+            diagnosis_scroll = diagnosis_scroll.push(Space::with_height(Length::Units(10)));
+
+            if let Some((slots, help)) = diagnosis.better_weapon_slots {
+                let template = if help {
+                    InterfaceSymbol::TemplateBetterWeaponSlotsHelp
+                } else {
+                    InterfaceSymbol::TemplateBetterWeaponSlotsDontHelp
+                };
+                let slots = slots.map(|slot| slot.to_string()).join("-");
+                diagnosis_scroll = diagnosis_scroll
+                    .push(Text::new(template.to_string().replace("{slots}", &slots)));
+            }
+
+            diagnosis_scroll = diagnosis_scroll.push(Text::new(match diagnosis.helpful_talisman {
+                Some((skill, level)) => InterfaceSymbol::TemplateHelpfulTalisman
+                    .to_string()
+                    .replace("{skill}", &LocalizedSkill(skill).to_string())
+                    .replace("{level}", &level.to_string()),
+                None => InterfaceSymbol::NoHelpfulTalisman.to_string(),
+            }));
+        }
+
+        pure::column()
+            .spacing(COLUMN_SPACING)
+            .padding(5)
+            .align_items(Alignment::Center)
+            .push(Text::new(InterfaceSymbol::Diagnose))
+            .push(pure::scrollable(diagnosis_scroll).height(Length::Fill))
+            .push(
+                pure::row().push(Space::with_width(Length::Fill)).push(
+                    pure::button(Text::new(InterfaceSymbol::Back))
+                        .on_press(Msg::ChangePage(Page::Main)),
+                ),
+            )
+    }
+}
@@ -0,0 +1,90 @@
+use super::{
+    common_elements::{
+        get_skill_filter, BUTTON_SPACING, COLUMN_SPACING, FILTER_INPUT_WIDTH, SCROLL_PADDING,
+    },
+    MainApp, Msg, Page,
+};
+use crate::style_iced;
+use iced::{pure, Alignment, Length, Space, Text};
+use rab::locale::InterfaceSymbol;
+
+pub trait JewelsPage {
+    fn get_jewels_page(&self) -> pure::widget::Column<'_, Msg>;
+}
+
+impl JewelsPage for MainApp {
+    fn get_jewels_page(&self) -> pure::widget::Column<'_, Msg> {
+        let mut jewels_scroll = pure::column()
+            .align_items(Alignment::Center)
+            .padding(SCROLL_PADDING)
+            .spacing(10);
+
+        // the filtered choices are already sorted by localized name
+        for skill in self
+            .filtered_wish_choices
+            .iter()
+            .copied()
+            .filter(|skill| skill.get_jewel_size().is_some())
+        {
+            let amount = self
+                .state
+                .jewel_inventory
+                .get(&skill.0)
+                .copied()
+                .unwrap_or(0);
+            jewels_scroll = jewels_scroll.push(
+                pure::row()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(Text::new(skill.to_string()).width(Length::Units(200)))
+                    // owning more jewels than the skill limit is useless for the search
+                    .push(
+                        pure::slider(0..=skill.get_limit(), amount, move |value| {
+                            Msg::JewelAmountChanged(skill.0, value)
+                        })
+                        .width(Length::Units(100)),
+                    )
+                    .push(Text::new(amount.to_string()).width(Length::Units(20))),
+            );
+        }
+
+        let top_row = pure::row()
+            .spacing(BUTTON_SPACING)
+            .align_items(Alignment::Center)
+            .push(
+                get_skill_filter(&self.value_filter_text_input)
+                    .width(Length::Units(FILTER_INPUT_WIDTH)),
+            )
+            .push(pure::checkbox(
+                InterfaceSymbol::IgnoreJewelInventory,
+                self.state.profile.ignore_jewel_inventory,
+                Msg::ToggleIgnoreJewelInventory,
+            ));
+
+        pure::column()
+            .spacing(COLUMN_SPACING)
+            .padding(5)
+            .align_items(Alignment::Center)
+            .push(top_row)
+            .push(pure::scrollable(jewels_scroll).height(Length::Fill))
+            .push(
+                pure::row()
+                    .spacing(BUTTON_SPACING)
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::DiscardModifications))
+                            .style(style_iced::Button::Remove)
+                            .on_press(Msg::DiscardJewels),
+                    )
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::SaveToFile))
+                            .style(style_iced::Button::Add)
+                            .on_press(Msg::SaveJewels),
+                    )
+                    .push(Space::with_width(Length::Fill))
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::Back))
+                            .on_press(Msg::ChangePage(Page::Main)),
+                    ),
+            )
+    }
+}
@@ -1,22 +1,22 @@
 use super::{common_elements::COLUMN_SPACING, MainApp, Msg, Page};
-use crate::locale::InterfaceSymbol;
 use iced::{pure, Alignment, Length, Space, Text};
+use rab::locale::InterfaceSymbol;
 
 pub trait LangPage {
-    fn get_lang_page(&self) -> pure::widget::Column<Msg>;
+    fn get_lang_page(&self) -> pure::widget::Column<'_, Msg>;
 }
 
 impl LangPage for MainApp {
-    fn get_lang_page(&self) -> pure::widget::Column<Msg> {
+    fn get_lang_page(&self) -> pure::widget::Column<'_, Msg> {
         let mut locales_choice = pure::column().spacing(COLUMN_SPACING);
 
-        for locale_name in self.locales.keys() {
+        for locale_name in self.state.locales.keys() {
             let button = pure::button(
                 pure::container(Text::new(locale_name))
                     .width(Length::Units(100))
                     .center_x(),
             );
-            locales_choice = locales_choice.push(if locale_name != &self.selected_locale {
+            locales_choice = locales_choice.push(if locale_name != &self.state.selected_locale {
                 button.on_press(Msg::LocaleChanged(locale_name.clone()))
             } else {
                 button
@@ -1,35 +1,84 @@
 use super::{
     common_elements::{
         armor_desc_to_element, get_column_builds_found, get_skill_filter, get_wishfield_row,
-        update_button, BUTTON_SPACING, COLUMN_SPACING, FILTER_INPUT_WIDTH, GLOBE_ICON, ICON_LENGTH,
-        LEFT_COLUMN_WIDTH, MOON_ICON, SCROLL_PADDING, SUN_ICON,
+        lock_button, update_button, BUTTON_SPACING, COLUMN_SPACING, FILTER_INPUT_WIDTH, GLOBE_ICON,
+        ICON_LENGTH, LEFT_COLUMN_WIDTH, MOON_ICON, SCROLL_PADDING, SUN_ICON,
     },
     MainApp, Msg, Page,
 };
-use crate::{locale::InterfaceSymbol, style_iced};
+use crate::style_iced;
 use iced::{pure, widget::svg::Handle, Alignment, Length, Radio, Space, Svg, Text};
+use lexical_sort::natural_lexical_cmp;
+use rab::{
+    build_stats::{count_jewels, missing_jewels, ArmorSlot, BuildSort},
+    locale::{InterfaceSymbol, LocalizedArmor},
+    search_form::{is_valid_preset_name, WishKind, MAX_WISH_WEIGHT},
+};
 use rab_core::armor_and_skills::Gender;
 
+// more free slots than that are rarely possible
+const MAX_FREE_SLOTS: u8 = 6;
+
 pub trait MainPage {
-    fn get_main_page(&self) -> pure::widget::Row<Msg>;
+    fn get_main_page(&self) -> pure::widget::Row<'_, Msg>;
 }
 
 impl MainPage for MainApp {
-    fn get_main_page(&self) -> pure::widget::Row<Msg> {
+    fn get_main_page(&self) -> pure::widget::Row<'_, Msg> {
         let mut scrollable_wishes = pure::column()
             .padding(SCROLL_PADDING)
             .spacing(10)
             .align_items(Alignment::Center);
         let size = self.wish_fields.len();
+        // the max levels are only known for the required wishes
+        let mut required_index = 0;
         for (key, wish_field) in self.wish_fields.iter().enumerate() {
+            let max_level = if wish_field.kind == WishKind::Required {
+                required_index += 1;
+                self.wish_max_levels
+                    .get(required_index - 1)
+                    .copied()
+                    .flatten()
+            } else {
+                None
+            };
             scrollable_wishes = scrollable_wishes.push(get_wishfield_row(
                 wish_field,
                 &self.filtered_wish_choices,
                 size <= 1,
+                max_level,
                 Msg::RemoveWish(key),
                 move |w| Msg::WishSelected(key, w),
                 move |value| Msg::SliderChanged(key, value),
             ));
+
+            let mut kind_row =
+                pure::row()
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .push(pure::pick_list(
+                        &WishKind::ALL[..],
+                        Some(wish_field.kind),
+                        move |kind| Msg::WishKindChanged(key, kind),
+                    ));
+            match wish_field.kind {
+                WishKind::Optional => {
+                    kind_row = kind_row
+                        .push(Text::new(InterfaceSymbol::Weight))
+                        .push(
+                            pure::slider(1..=MAX_WISH_WEIGHT, wish_field.weight, move |weight| {
+                                Msg::WishWeightChanged(key, weight)
+                            })
+                            .width(Length::Units(100)),
+                        )
+                        .push(Text::new(wish_field.weight.to_string()))
+                }
+                WishKind::Avoid => {
+                    kind_row = kind_row.push(Text::new(InterfaceSymbol::HighestAllowedLevel))
+                }
+                WishKind::Required => (),
+            }
+            scrollable_wishes = scrollable_wishes.push(kind_row);
         }
 
         let filter_text_input = get_skill_filter(&self.value_filter_text_input)
@@ -64,6 +113,14 @@ impl MainPage for MainApp {
             .style(style_iced::Button::Talisman)
             .on_press(Msg::ChangePage(Page::Talisman));
 
+        let blacklist_button = pure::button(Text::new(InterfaceSymbol::ManageExclusions))
+            .style(style_iced::Button::Talisman)
+            .on_press(Msg::ChangePage(Page::Blacklist));
+
+        let jewels_button = pure::button(Text::new(InterfaceSymbol::ManageJewels))
+            .style(style_iced::Button::Talisman)
+            .on_press(Msg::ChangePage(Page::Jewels));
+
         let search_button = pure::button(Text::new(InterfaceSymbol::SearchBuilds))
             .style(style_iced::Button::Search)
             .on_press(Msg::Search);
@@ -72,7 +129,9 @@ impl MainPage for MainApp {
             .align_items(Alignment::End)
             .spacing(BUTTON_SPACING)
             .push(builds_menu_button)
-            .push(talisman_button);
+            .push(talisman_button)
+            .push(blacklist_button)
+            .push(jewels_button);
         let col_other_buttons = pure::column()
             .spacing(BUTTON_SPACING)
             .push(add_wish_button)
@@ -83,6 +142,75 @@ impl MainPage for MainApp {
             .push(col_other_buttons)
             .push(col_menu_buttons);
 
+        let mut preset_names: Vec<String> = self.state.presets.keys().cloned().collect();
+        preset_names.sort_unstable_by(|a, b| natural_lexical_cmp(a, b));
+
+        let mut save_preset_button =
+            pure::button(Text::new(InterfaceSymbol::Save)).style(style_iced::Button::Add);
+        let mut rename_preset_button = pure::button(Text::new(InterfaceSymbol::Rename));
+        let mut delete_preset_button =
+            pure::button(Text::new(InterfaceSymbol::Remove)).style(style_iced::Button::Remove);
+        if is_valid_preset_name(
+            &self.state.presets,
+            &self.value_preset_name_text_input,
+            self.selected_preset.as_deref(),
+        ) {
+            save_preset_button = save_preset_button.on_press(Msg::SavePreset);
+            if self.selected_preset.is_some() {
+                rename_preset_button = rename_preset_button.on_press(Msg::RenamePreset);
+            }
+        }
+        if self.selected_preset.is_some() {
+            delete_preset_button = delete_preset_button.on_press(Msg::DeletePreset);
+        }
+
+        let row_presets = pure::row()
+            .spacing(5)
+            .align_items(Alignment::Center)
+            .push(
+                pure::pick_list(
+                    preset_names,
+                    self.selected_preset.clone(),
+                    Msg::PresetSelected,
+                )
+                .placeholder(InterfaceSymbol::Presets.to_string())
+                .width(Length::Units(130)),
+            )
+            .push(
+                pure::text_input(
+                    &InterfaceSymbol::PresetName.to_string(),
+                    &self.value_preset_name_text_input,
+                    Msg::PresetNameChanged,
+                )
+                .padding(5)
+                .width(Length::Units(110)),
+            )
+            .push(save_preset_button)
+            .push(rename_preset_button)
+            .push(delete_preset_button);
+
+        let row_query_code = pure::row()
+            .spacing(5)
+            .align_items(Alignment::Center)
+            .push(
+                pure::text_input(
+                    &InterfaceSymbol::QueryCode.to_string(),
+                    &self.value_query_code_text_input,
+                    Msg::QueryCodeChanged,
+                )
+                .on_submit(Msg::ImportQueryCode)
+                .padding(5)
+                .width(Length::Units(130)),
+            )
+            .push(
+                pure::button(Text::new(InterfaceSymbol::ImportFromCode))
+                    .on_press(Msg::ImportQueryCode),
+            )
+            .push(
+                pure::button(Text::new(InterfaceSymbol::CopyQueryCode))
+                    .on_press(Msg::CopyQueryCode),
+            );
+
         let mut sliders_weapon_slot = pure::row()
             .spacing(5)
             .push(Text::new(InterfaceSymbol::WeaponSlots).width(Length::Units(105)));
@@ -95,22 +223,189 @@ impl MainPage for MainApp {
                 .push(Text::new(value.to_string()))
         }
 
-        let column_left = pure::column()
+        let mut sliders_free_slot = pure::row()
+            .spacing(5)
+            .push(Text::new(InterfaceSymbol::FreeSlots).width(Length::Units(105)));
+
+        for (index, value) in self.states_values_slider_free_slot.iter().enumerate() {
+            sliders_free_slot = sliders_free_slot
+                .push(Text::new(
+                    InterfaceSymbol::TemplateSlotLevel
+                        .to_string()
+                        .replace("{size}", &(index + 1).to_string()),
+                ))
+                .push(pure::slider(0..=MAX_FREE_SLOTS, *value, move |v| {
+                    Msg::FreeSlotChanged(index, v)
+                }))
+                .push(Text::new(value.to_string()))
+        }
+
+        let mut min_stats_first_row =
+            pure::row()
+                .spacing(5)
+                .align_items(Alignment::Center)
+                .push(min_stat_input(
+                    style_iced::Container::Defense,
+                    InterfaceSymbol::Defense,
+                    &self.value_min_defense_text_input,
+                    Msg::MinDefenseChanged,
+                ));
+        let mut min_stats_second_row = pure::row().spacing(5).align_items(Alignment::Center);
+
+        for (index, (style, name)) in [
+            (style_iced::Container::Fire, InterfaceSymbol::Fire),
+            (style_iced::Container::Water, InterfaceSymbol::Water),
+            (style_iced::Container::Thunder, InterfaceSymbol::Thunder),
+            (style_iced::Container::Ice, InterfaceSymbol::Ice),
+            (style_iced::Container::Dragon, InterfaceSymbol::Dragon),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let input = min_stat_input(
+                style,
+                name,
+                &self.values_min_resistance_text_input[index],
+                move |text| Msg::MinResistanceChanged(index, text),
+            );
+            if index < 2 {
+                min_stats_first_row = min_stats_first_row.push(input);
+            } else {
+                min_stats_second_row = min_stats_second_row.push(input);
+            }
+        }
+
+        let missing = match &self.armor_desc {
+            Some((_, jewels)) if !self.state.profile.ignore_jewel_inventory => {
+                missing_jewels(&count_jewels([jewels]), &self.state.jewel_inventory)
+            }
+            _ => vec![],
+        };
+        let mut armor_desc = armor_desc_to_element(&self.armor_desc, &missing);
+        if let (Some(slot), Some((armor, _))) = (self.armor_desc_slot, &self.armor_desc) {
+            armor_desc = armor_desc.push(lock_button(slot, armor, &self.state.locked_armors));
+        }
+
+        let mut column_left = pure::column()
             .spacing(COLUMN_SPACING)
             .push(buttons)
+            .push(row_presets)
+            .push(row_query_code);
+        if let Some(error) = &self.query_code_error {
+            column_left = column_left.push(Text::new(error.as_str()));
+        }
+        column_left = column_left
             .push(row_gender_radio_and_filter)
-            .push(pure::scrollable(scrollable_wishes).height(Length::FillPortion(2)))
-            .push(
-                pure::scrollable(armor_desc_to_element(&self.armor_desc))
-                    .height(Length::FillPortion(3)),
-            )
+            .push(pure::scrollable(scrollable_wishes).height(Length::FillPortion(2)));
+
+        if self.state.locked_armors.iter().any(Option::is_some) {
+            let mut locked_pieces = pure::column()
+                .spacing(5)
+                .align_items(Alignment::Center)
+                .push(Text::new(InterfaceSymbol::LockedPieces));
+            for (slot, armor) in ArmorSlot::ALL.iter().zip(&self.state.locked_armors) {
+                if let Some(armor) = armor {
+                    locked_pieces = locked_pieces.push(
+                        pure::row()
+                            .spacing(10)
+                            .align_items(Alignment::Center)
+                            .push(Text::new(format!(
+                                "{}: {}",
+                                slot.symbol(),
+                                LocalizedArmor(armor)
+                            )))
+                            .push(
+                                pure::button(Text::new(InterfaceSymbol::Unlock))
+                                    .style(style_iced::Button::Remove)
+                                    .on_press(Msg::UnlockArmor(*slot)),
+                            ),
+                    );
+                }
+            }
+            column_left = column_left.push(locked_pieces);
+        }
+
+        let column_left = column_left
+            .push(pure::scrollable(armor_desc).height(Length::FillPortion(3)))
             .push(Space::with_height(Length::Fill))
+            .push(Text::new(InterfaceSymbol::Minimums))
+            .push(min_stats_first_row)
+            .push(min_stats_second_row)
             .push(sliders_weapon_slot)
+            .push(sliders_free_slot)
+            .push(pure::checkbox(
+                InterfaceSymbol::IgnoreJewelInventory,
+                self.state.profile.ignore_jewel_inventory,
+                Msg::ToggleIgnoreJewelInventory,
+            ))
+            .push(pure::checkbox(
+                InterfaceSymbol::RestoreResults,
+                self.state.profile.restore_results,
+                Msg::ToggleRestoreResults,
+            ))
             .align_items(Alignment::Center);
 
-        let column_right = pure::column()
-            .spacing(10)
-            .push(get_column_builds_found(&self.builds).height(Length::Fill))
+        let mut column_right = pure::column().spacing(10);
+
+        if let Some(search) = &self.running_search {
+            column_right = column_right.push(
+                pure::row()
+                    .spacing(BUTTON_SPACING)
+                    .align_items(Alignment::Center)
+                    .push(pure::progress_bar(0.0..=1.0, search.progress()))
+                    .push(Text::new(
+                        InterfaceSymbol::TemplateBuildsFoundSoFar
+                            .to_string()
+                            .replace("{count}", &self.state.results.builds.len().to_string()),
+                    ))
+                    .push(
+                        pure::button(Text::new(InterfaceSymbol::Cancel))
+                            .style(style_iced::Button::Cancel)
+                            .on_press(Msg::CancelSearch),
+                    ),
+            );
+        }
+
+        let mut additional_skills_button =
+            pure::button(Text::new(InterfaceSymbol::AdditionalSkills))
+                .style(style_iced::Button::Search);
+        // the additional skills are searched on top of found builds
+        if self.running_search.is_none() && !self.state.results.builds.is_empty() {
+            additional_skills_button = additional_skills_button.on_press(Msg::FindAdditionalSkills);
+        }
+
+        let mut diagnose_button =
+            pure::button(Text::new(InterfaceSymbol::Diagnose)).style(style_iced::Button::Search);
+        // searched_wishes is empty until the first search
+        if self.running_search.is_none()
+            && self.state.results.builds.is_empty()
+            && !self.state.results.wishes.is_empty()
+        {
+            diagnose_button = diagnose_button.on_press(Msg::Diagnose);
+        }
+
+        let column_right = column_right
+            .push(
+                pure::row()
+                    .spacing(5)
+                    .align_items(Alignment::Center)
+                    .push(additional_skills_button)
+                    .push(diagnose_button)
+                    .push(Space::with_width(Length::Fill))
+                    .push(Text::new(InterfaceSymbol::SortBy))
+                    .push(pure::pick_list(
+                        &BuildSort::ALL[..],
+                        Some(self.state.profile.sort),
+                        Msg::SortChanged,
+                    )),
+            )
+            .push(
+                get_column_builds_found(
+                    &self.state.results.builds,
+                    &self.state.results.optional_wishes,
+                )
+                .height(Length::Fill),
+            )
             .push(
                 pure::row()
                     .height(ICON_LENGTH)
@@ -143,3 +438,25 @@ impl MainPage for MainApp {
             .push(column_right)
     }
 }
+
+fn min_stat_input<'a>(
+    style: style_iced::Container,
+    name: InterfaceSymbol,
+    value: &str,
+    on_change: impl Fn(String) -> Msg + 'a,
+) -> pure::widget::Row<'a, Msg> {
+    pure::row()
+        .spacing(5)
+        .align_items(Alignment::Center)
+        .push(
+            pure::container(Text::new(name))
+                .width(Length::Units(70))
+                .center_x()
+                .style(style),
+        )
+        .push(
+            pure::text_input("", value, on_change)
+                .padding(5)
+                .width(Length::Units(50)),
+        )
+}
@@ -5,11 +5,11 @@ use super::{
 use iced::pure;
 
 pub trait NoFilesPage {
-    fn get_no_files_page(&self) -> pure::widget::Button<Msg>;
+    fn get_no_files_page(&self) -> pure::widget::Button<'_, Msg>;
 }
 
 impl NoFilesPage for MainApp {
-    fn get_no_files_page(&self) -> pure::widget::Button<Msg> {
+    fn get_no_files_page(&self) -> pure::widget::Button<'_, Msg> {
         update_button(self.update_state, Msg::DownloadArmors).height(ICON_LENGTH)
     }
 }
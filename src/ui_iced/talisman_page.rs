@@ -5,19 +5,20 @@ use super::{
     },
     MainApp, Msg, Page, WishField,
 };
-use crate::{
+use crate::style_iced;
+use iced::{pure, Alignment, Length, Space, Text};
+use rab::{
+    export::slot_text,
     locale::{InterfaceSymbol, LocalizedSkill},
-    style_iced,
 };
-use iced::{pure, Alignment, Length, Space, Text};
 use rab_core::armor_and_skills::Armor;
 
 pub trait TalismanPage {
-    fn get_talisman_page(&self) -> pure::widget::Row<Msg>;
+    fn get_talisman_page(&self) -> pure::widget::Row<'_, Msg>;
 }
 
 impl TalismanPage for MainApp {
-    fn get_talisman_page(&self) -> pure::widget::Row<Msg> {
+    fn get_talisman_page(&self) -> pure::widget::Row<'_, Msg> {
         let back_button = pure::button(
             pure::container(Text::new(InterfaceSymbol::Back))
                 .center_x()
@@ -40,7 +41,7 @@ impl TalismanPage for MainApp {
             .padding(SCROLL_PADDING)
             .spacing(10);
 
-        for (index, talisman) in self.talismans.iter().enumerate() {
+        for (index, talisman) in self.state.talismans.iter().enumerate() {
             let mut button =
                 pure::button(Text::new(&talisman.name)).style(style_iced::Button::Result);
             if !self.is_editing {
@@ -94,7 +95,7 @@ impl TalismanPage for MainApp {
                             ),
                     )
             } else {
-                let talisman_desc = talisman_to_element(&self.talismans[*index]);
+                let talisman_desc = talisman_to_element(&self.state.talismans[*index]);
                 pure::column()
                     .align_items(Alignment::Center)
                     .push(
@@ -140,7 +141,10 @@ impl TalismanPage for MainApp {
                     .push(save_button),
             )
             .align_items(Alignment::Center);
-        let column_right = get_column_builds_found(&self.builds);
+        let column_right = get_column_builds_found(
+            &self.state.results.builds,
+            &self.state.results.optional_wishes,
+        );
         pure::row()
             .padding(5)
             .push(column_left.width(Length::Units(LEFT_COLUMN_WIDTH)))
@@ -187,6 +191,7 @@ fn get_talisman_editor<'a>(
             wish_fields,
             skill_list,
             false,
+            None,
             Msg::EditRemoveSkill(index),
             move |skill| Msg::EditSkillSelected(index, skill),
             move |v| Msg::EditSkillSliderChanged(index, v),
@@ -223,14 +228,10 @@ fn talisman_to_element<'a>(talisman: &Armor) -> pure::widget::Scrollable<'a, Msg
 
     for slot in talisman.slots.iter() {
         talisman_desc = talisman_desc.push(
-            pure::container(Text::new(
-                InterfaceSymbol::TemplateFreeSlot
-                    .to_string()
-                    .replace("{size}", &slot.to_string()),
-            ))
-            .width(Length::Units(170))
-            .center_x()
-            .style(style_iced::Container::Ice),
+            pure::container(Text::new(slot_text(*slot, None)))
+                .width(Length::Units(170))
+                .center_x()
+                .style(style_iced::Container::Ice),
         )
     }
 
@@ -0,0 +1,905 @@
+use std::{
+    io::{self, Stdout},
+    panic,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use crossterm::{
+    cursor::Show,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use rab_core::{
+    armor_and_skills::{Gender, Skill},
+    build_search::Build,
+};
+use tui::{
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+
+use rab::{
+    app_state::{skill_totals, AppState, Messages, TalismanDraft},
+    build_stats::{build_parts, place_jewels, total_defense, total_resistances, ArmorSlot},
+    export::slot_text,
+    locale::{InterfaceSymbol, LocalizedArmor, LocalizedSkill},
+    paths::Paths,
+    search::SearchJob,
+    search_form::{FormWish, SearchForm, WishKind},
+};
+
+// same limits as the sliders of the iced interface
+const MAX_WEAPON_SLOT: u8 = 3;
+const MAX_TALISMAN_SKILLS: usize = 2;
+
+pub const USAGE: &str = "Usage: rab-tui [OPTIONS]
+
+Options:
+  --data-dir <DIR>     Directory of every RAB file (profile included)
+  --armors-dir <DIR>   Directory of the armor files
+  --locale-dir <DIR>   Directory of the locale files
+  --lang <NAME>        Language for this launch, like English
+  -h, --help           Print this help";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Page {
+    Main,
+    Results,
+    Details(bool), // true for a saved build
+    Builds,
+    Talismans,
+    TalismanEditor,
+}
+
+// The skill picker replaces the page until a skill is chosen
+#[derive(Clone, Copy)]
+enum PickerTarget {
+    NewWish,
+    Wish(usize),
+    NewTalismanSkill,
+    TalismanSkill(usize),
+}
+
+struct SkillPicker {
+    target: PickerTarget,
+    filter: String,
+    list: ListState,
+}
+
+// the steps are run one after another in another thread
+struct RunningSearch {
+    job: Arc<SearchJob>,
+    receiver: Receiver<Option<Vec<Build>>>,
+    done_steps: usize,
+}
+
+struct TuiApp {
+    state: AppState,
+    page: Page,
+
+    form: SearchForm,
+    skills: Vec<LocalizedSkill>, // sorted in the current language
+    main_list: ListState,        // the wishes, then the gender and the weapon slots
+
+    picker: Option<SkillPicker>,
+
+    running_search: Option<RunningSearch>,
+    results_list: ListState,
+
+    focused_build: Option<Build>,
+    build_name: Option<String>, // Some while the name is typed
+    saved_build_name: String,
+    builds_list: ListState,
+
+    talismans_list: ListState,
+    draft: TalismanDraft,
+    draft_list: ListState, // the name, the skills, then the slots
+
+    status: String,
+    quit: bool,
+}
+
+pub fn run(paths: Paths, lang: Option<String>) -> io::Result<()> {
+    let (state, form) = AppState::load(paths, lang, Messages::kept());
+    let mut app = TuiApp::new(state, form);
+
+    // a panic must not leave the terminal unusable
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, Show);
+        default_hook(info);
+    }));
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = event_loop(&mut terminal, &mut app);
+
+    // the terminal must be restored even after an error
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    app.save_session();
+    // the interface is closed, the last messages are printed
+    for message in app.state.messages.take() {
+        println!("{}", message);
+    }
+
+    result
+}
+
+fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    app: &mut TuiApp,
+) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        // the events are polled to show the progress of the search
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                app.on_key(key);
+            }
+        }
+        app.receive_builds();
+        app.show_messages();
+    }
+    if let Some(search) = app.running_search.take() {
+        search.job.cancel();
+    }
+    Ok(())
+}
+
+impl TuiApp {
+    fn new(state: AppState, form: Option<SearchForm>) -> Self {
+        let skills = state.sorted_skills();
+        let mut app = Self {
+            page: Page::Main,
+            state,
+            form: form.unwrap_or_default(),
+            skills,
+            main_list: ListState::default(),
+            picker: None,
+            running_search: None,
+            results_list: ListState::default(),
+            focused_build: None,
+            build_name: None,
+            saved_build_name: String::new(),
+            builds_list: ListState::default(),
+            talismans_list: ListState::default(),
+            draft: TalismanDraft::default(),
+            draft_list: ListState::default(),
+            status: String::new(),
+            quit: false,
+        };
+        app.main_list.select(Some(0));
+        app.show_messages();
+        if app.state.armors_missing {
+            app.status = "No armor file, download them with the iced interface".to_string();
+        }
+        app
+    }
+
+    // only the last message fits in the status line
+    fn show_messages(&mut self) {
+        if let Some(message) = self.state.messages.take().pop() {
+            self.status = message.replace('\n', " ");
+        }
+    }
+
+    fn main_row_count(&self) -> usize {
+        // gender + 3 weapon slots
+        self.form.wishes.len() + 4
+    }
+
+    // saved by each search and on exit like the iced interface
+    fn save_session(&self) {
+        self.state.save_session(self.form.clone());
+    }
+
+    fn start_search(&mut self) {
+        if let Some(search) = self.running_search.take() {
+            search.job.cancel();
+        }
+        let job = Arc::new(self.state.start_search(&self.form));
+        self.save_session();
+        let (sender, receiver) = mpsc::channel();
+        let thread_job = job.clone();
+        thread::spawn(move || {
+            for step in 0..thread_job.step_count() {
+                let builds = thread_job.run_step(step);
+                let cancelled = builds.is_none();
+                // the receiver is dropped if the search is replaced
+                if sender.send(builds).is_err() || cancelled {
+                    return;
+                }
+            }
+        });
+        self.running_search = Some(RunningSearch {
+            job,
+            receiver,
+            done_steps: 0,
+        });
+        self.results_list.select(None);
+        self.page = Page::Results;
+    }
+
+    fn receive_builds(&mut self) {
+        let search = match &mut self.running_search {
+            Some(search) => search,
+            None => return,
+        };
+        let mut found = Vec::new();
+        let mut finished = false;
+        loop {
+            match search.receiver.try_recv() {
+                Ok(Some(builds)) => {
+                    found.extend(builds);
+                    search.done_steps += 1;
+                }
+                Ok(None) | Err(TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        finished |= search.done_steps == search.job.step_count();
+        if !found.is_empty() {
+            self.state.add_builds(found);
+        }
+        if finished {
+            self.running_search = None;
+            if self.state.profile.restore_results {
+                self.save_session()
+            }
+        }
+        if self.results_list.selected().is_none() && !self.state.results.builds.is_empty() {
+            self.results_list.select(Some(0));
+        }
+    }
+
+    fn focus_build(&mut self, build: Build, saved_name: Option<String>) {
+        self.focused_build = Some(build);
+        self.page = Page::Details(saved_name.is_some());
+        self.saved_build_name = saved_name.unwrap_or_default();
+    }
+
+    fn sorted_build_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.state.saved_builds.keys().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        self.status.clear();
+        if self.picker.is_some() {
+            return self.on_picker_key(key);
+        }
+        if self.build_name.is_some() {
+            return self.on_build_name_key(key);
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+        match self.page {
+            Page::Main => self.on_main_key(key),
+            Page::Results => self.on_results_key(key),
+            Page::Details(saved) => self.on_details_key(key, saved),
+            Page::Builds => self.on_builds_key(key),
+            Page::Talismans => self.on_talismans_key(key),
+            Page::TalismanEditor => self.on_editor_key(key),
+        }
+    }
+
+    fn open_picker(&mut self, target: PickerTarget) {
+        let mut list = ListState::default();
+        list.select(Some(0));
+        self.picker = Some(SkillPicker {
+            target,
+            filter: String::new(),
+            list,
+        });
+    }
+
+    fn filtered_skills(&self, filter: &str) -> Vec<LocalizedSkill> {
+        let filter = filter.to_lowercase();
+        self.skills
+            .iter()
+            .copied()
+            .filter(|skill| skill.to_string().to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn on_picker_key(&mut self, key: KeyEvent) {
+        let picker = self.picker.as_mut().unwrap();
+        let selected = picker.list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc => self.picker = None,
+            KeyCode::Up => picker.list.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => picker.list.select(Some(selected + 1)),
+            KeyCode::Backspace => {
+                picker.filter.pop();
+                picker.list.select(Some(0));
+            }
+            KeyCode::Char(c) => {
+                picker.filter.push(c);
+                picker.list.select(Some(0));
+            }
+            KeyCode::Enter => {
+                let picker = self.picker.take().unwrap();
+                let skill = match self.filtered_skills(&picker.filter).get(selected) {
+                    Some(skill) => skill.0,
+                    None => return,
+                };
+                self.pick_skill(picker.target, skill);
+            }
+            _ => (),
+        }
+        // the selection stays in the filtered list
+        if let Some(filter) = self.picker.as_ref().map(|picker| picker.filter.clone()) {
+            let count = self.filtered_skills(&filter).len();
+            let list = &mut self.picker.as_mut().unwrap().list;
+            if let Some(selected) = list.selected() {
+                list.select(Some(selected.min(count.saturating_sub(1))));
+            }
+        }
+    }
+
+    fn pick_skill(&mut self, target: PickerTarget, skill: Skill) {
+        match target {
+            PickerTarget::NewWish => {
+                self.form.wishes.push(FormWish {
+                    skill,
+                    level: 1,
+                    kind: WishKind::Required,
+                    weight: 1,
+                });
+                self.main_list.select(Some(self.form.wishes.len() - 1));
+            }
+            PickerTarget::Wish(index) => {
+                self.form.wishes[index].skill = skill;
+            }
+            PickerTarget::NewTalismanSkill => self.draft.skills.push((skill, 1)),
+            PickerTarget::TalismanSkill(index) => self.draft.skills[index] = (skill, 1),
+        }
+    }
+
+    fn on_main_key(&mut self, key: KeyEvent) {
+        let row = self.main_list.selected().unwrap_or(0);
+        let wish_count = self.form.wishes.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up => self.main_list.select(Some(row.saturating_sub(1))),
+            KeyCode::Down => self
+                .main_list
+                .select(Some((row + 1).min(self.main_row_count() - 1))),
+            KeyCode::Left | KeyCode::Right => {
+                let increase = key.code == KeyCode::Right;
+                let step = |value: u8, max: u8| {
+                    if increase {
+                        (value + 1).min(max)
+                    } else {
+                        value.saturating_sub(1)
+                    }
+                };
+                if row < wish_count {
+                    let wish = &mut self.form.wishes[row];
+                    // an avoided skill can be forbidden with the level 0
+                    let min = if wish.kind == WishKind::Avoid { 0 } else { 1 };
+                    wish.level = step(wish.level, wish.skill.get_limit()).max(min);
+                } else if row == wish_count {
+                    self.form.gender = match self.form.gender {
+                        Gender::Female => Gender::Male,
+                        Gender::Male => Gender::Female,
+                    };
+                } else {
+                    let slot = &mut self.form.weapon_slots[row - wish_count - 1];
+                    *slot = step(*slot, MAX_WEAPON_SLOT);
+                }
+            }
+            KeyCode::Char('a') => self.open_picker(PickerTarget::NewWish),
+            KeyCode::Enter if row < wish_count => self.open_picker(PickerTarget::Wish(row)),
+            KeyCode::Char('d') | KeyCode::Delete if row < wish_count => {
+                self.form.wishes.remove(row);
+                self.main_list
+                    .select(Some(row.min(self.main_row_count() - 1)));
+            }
+            KeyCode::Char('k') if row < wish_count => {
+                let wish = &mut self.form.wishes[row];
+                let index = WishKind::ALL.iter().position(|kind| *kind == wish.kind);
+                wish.kind = WishKind::ALL[(index.unwrap_or(0) + 1) % WishKind::ALL.len()];
+                wish.level = match wish.kind {
+                    WishKind::Avoid => 0,
+                    _ => wish.level.max(1),
+                };
+            }
+            KeyCode::Char('s') => {
+                if self.form.wishes.is_empty() {
+                    self.status = "Add a wish first".to_string();
+                } else {
+                    self.start_search();
+                }
+            }
+            KeyCode::Char('r') => self.page = Page::Results,
+            KeyCode::Char('b') => {
+                self.builds_list.select(Some(0));
+                self.page = Page::Builds;
+            }
+            KeyCode::Char('t') => {
+                self.talismans_list.select(Some(0));
+                self.page = Page::Talismans;
+            }
+            _ => (),
+        }
+    }
+
+    fn on_results_key(&mut self, key: KeyEvent) {
+        let count = self.state.results.builds.len();
+        let row = self.results_list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.page = Page::Main,
+            KeyCode::Up => self.results_list.select(Some(row.saturating_sub(1))),
+            KeyCode::Down if count > 0 => self.results_list.select(Some((row + 1).min(count - 1))),
+            KeyCode::Char('c') => {
+                if let Some(search) = self.running_search.take() {
+                    search.job.cancel();
+                }
+            }
+            KeyCode::Enter if row < count => {
+                let build = self.state.results.builds[row].clone();
+                self.focus_build(build, None)
+            }
+            _ => (),
+        }
+    }
+
+    fn on_details_key(&mut self, key: KeyEvent, saved: bool) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.page = if saved { Page::Builds } else { Page::Results }
+            }
+            KeyCode::Char('s') => self.build_name = Some(self.saved_build_name.clone()),
+            KeyCode::Char('d') if saved => {
+                self.state.remove_saved_build(&self.saved_build_name);
+                self.page = Page::Builds;
+            }
+            _ => (),
+        }
+    }
+
+    // a saved build is renamed, the others are saved
+    fn on_build_name_key(&mut self, key: KeyEvent) {
+        let name = self.build_name.as_mut().unwrap();
+        match key.code {
+            KeyCode::Esc => self.build_name = None,
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Enter if !name.is_empty() => {
+                let name = self.build_name.take().unwrap();
+                if self.page == Page::Details(true) {
+                    self.state
+                        .rename_saved_build(&self.saved_build_name, name.clone());
+                } else {
+                    self.state
+                        .save_build(name.clone(), self.focused_build.clone().unwrap());
+                }
+                self.status = format!("{}: {}", InterfaceSymbol::SaveBuild, name);
+                self.saved_build_name = name;
+                self.page = Page::Details(true);
+            }
+            _ => (),
+        }
+    }
+
+    fn on_builds_key(&mut self, key: KeyEvent) {
+        let names = self.sorted_build_names();
+        let row = self.builds_list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.page = Page::Main,
+            KeyCode::Up => self.builds_list.select(Some(row.saturating_sub(1))),
+            KeyCode::Down if !names.is_empty() => self
+                .builds_list
+                .select(Some((row + 1).min(names.len() - 1))),
+            KeyCode::Enter if row < names.len() => {
+                let name = names[row].clone();
+                let build = self.state.saved_builds[&name].clone();
+                self.focus_build(build, Some(name));
+            }
+            KeyCode::Char('d') | KeyCode::Delete if row < names.len() => {
+                self.state.remove_saved_build(&names[row]);
+                self.builds_list.select(Some(row.saturating_sub(1)));
+            }
+            _ => (),
+        }
+    }
+
+    fn on_talismans_key(&mut self, key: KeyEvent) {
+        let count = self.state.talismans.len();
+        let row = self.talismans_list.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.page = Page::Main,
+            KeyCode::Up => self.talismans_list.select(Some(row.saturating_sub(1))),
+            KeyCode::Down if count > 0 => {
+                self.talismans_list.select(Some((row + 1).min(count - 1)))
+            }
+            KeyCode::Char('a') => {
+                self.state.add_talisman();
+                self.talismans_list.select(Some(count));
+            }
+            KeyCode::Char('d') | KeyCode::Delete if row < count => {
+                self.state.talismans.remove(row);
+                self.talismans_list.select(Some(row.saturating_sub(1)));
+            }
+            KeyCode::Enter if row < count => {
+                self.draft = TalismanDraft::from_talisman(&self.state.talismans[row]);
+                self.draft_list.select(Some(0));
+                self.page = Page::TalismanEditor;
+            }
+            KeyCode::Char('w') => {
+                self.state.save_talismans();
+                self.status = InterfaceSymbol::SaveToFile.to_string();
+            }
+            KeyCode::Char('u') => {
+                self.state.reload_talismans();
+                self.talismans_list.select(Some(0));
+                self.status = InterfaceSymbol::DiscardModifications.to_string();
+            }
+            _ => (),
+        }
+    }
+
+    // rows: the name, the skills, then the slots
+    fn on_editor_key(&mut self, key: KeyEvent) {
+        let row = self.draft_list.selected().unwrap_or(0);
+        let skill_count = self.draft.skills.len();
+        let row_count = 1 + skill_count + self.draft.slots.len();
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.page = Page::Talismans,
+            KeyCode::Char('s') if control => {
+                if let Some(index) = self.talismans_list.selected() {
+                    self.draft.apply(&mut self.state.talismans[index]);
+                }
+                self.page = Page::Talismans;
+            }
+            KeyCode::Up => self.draft_list.select(Some(row.saturating_sub(1))),
+            KeyCode::Down => self.draft_list.select(Some((row + 1).min(row_count - 1))),
+            KeyCode::Backspace if row == 0 => {
+                self.draft.name.pop();
+            }
+            KeyCode::Char(c) if row == 0 => self.draft.name.push(c),
+            KeyCode::Left | KeyCode::Right if row > 0 => {
+                let increase = key.code == KeyCode::Right;
+                let step = |value: u8, min: u8, max: u8| {
+                    if increase {
+                        (value + 1).min(max)
+                    } else {
+                        value.saturating_sub(1).max(min)
+                    }
+                };
+                if row <= skill_count {
+                    let (skill, level) = &mut self.draft.skills[row - 1];
+                    *level = step(*level, 1, skill.get_limit());
+                } else {
+                    let slot = &mut self.draft.slots[row - 1 - skill_count];
+                    *slot = step(*slot, 0, MAX_WEAPON_SLOT);
+                }
+            }
+            KeyCode::Enter if row > 0 && row <= skill_count => {
+                self.open_picker(PickerTarget::TalismanSkill(row - 1))
+            }
+            KeyCode::Char('a') if skill_count < MAX_TALISMAN_SKILLS => {
+                self.open_picker(PickerTarget::NewTalismanSkill)
+            }
+            KeyCode::Char('d') | KeyCode::Delete if row > 0 && row <= skill_count => {
+                self.draft.skills.remove(row - 1);
+            }
+            _ => (),
+        }
+    }
+
+    fn draw<B: Backend>(&mut self, frame: &mut Frame<B>) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(2)])
+            .split(frame.size());
+
+        if self.picker.is_some() {
+            self.draw_picker(frame, chunks[0]);
+        } else {
+            match self.page {
+                Page::Main => self.draw_main(frame, chunks[0]),
+                Page::Results => self.draw_results(frame, chunks[0]),
+                Page::Details(_) => self.draw_details(frame, chunks[0]),
+                Page::Builds => self.draw_builds(frame, chunks[0]),
+                Page::Talismans => self.draw_talismans(frame, chunks[0]),
+                Page::TalismanEditor => self.draw_editor(frame, chunks[0]),
+            }
+        }
+
+        let help = if self.picker.is_some() {
+            "type to filter  ↑↓ select  Enter choose  Esc cancel"
+        } else if self.build_name.is_some() {
+            "type the name  Enter save  Esc cancel"
+        } else {
+            match self.page {
+                Page::Main => "a add wish  Enter change skill  ←→ level  k kind  d remove  s search  r results  b builds  t talismans  q quit",
+                Page::Results => "↑↓ select  Enter details  c cancel search  Esc back",
+                Page::Details(true) => "s rename  d remove  Esc back",
+                Page::Details(false) => "s save  Esc back",
+                Page::Builds => "↑↓ select  Enter details  d remove  Esc back",
+                Page::Talismans => "Enter edit  a add  d remove  w save to file  u discard modifications  Esc back",
+                Page::TalismanEditor => "type the name  ←→ level/size  Enter change skill  a add skill  d remove skill  Ctrl+S save  Esc cancel",
+            }
+        };
+        let footer = Paragraph::new(vec![
+            Spans::from(Span::styled(
+                self.status.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+            Spans::from(help),
+        ]);
+        frame.render_widget(footer, chunks[1]);
+    }
+
+    fn draw_main<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let mut items: Vec<ListItem> = self
+            .form
+            .wishes
+            .iter()
+            .map(|wish| {
+                let level = match wish.kind {
+                    WishKind::Avoid => {
+                        format!("{} {}", InterfaceSymbol::HighestAllowedLevel, wish.level)
+                    }
+                    _ => wish.level.to_string(),
+                };
+                ListItem::new(format!(
+                    "{:<30} {:<10} {}",
+                    LocalizedSkill(wish.skill).to_string(),
+                    wish.kind.to_string(),
+                    level
+                ))
+            })
+            .collect();
+        items.push(ListItem::new(match self.form.gender {
+            Gender::Female => InterfaceSymbol::Female.to_string(),
+            Gender::Male => InterfaceSymbol::Male.to_string(),
+        }));
+        for (index, slot) in self.form.weapon_slots.iter().enumerate() {
+            items.push(ListItem::new(format!(
+                "{} {}: {}",
+                InterfaceSymbol::WeaponSlots,
+                index + 1,
+                slot
+            )));
+        }
+
+        let list = List::new(items)
+            .block(titled_block(InterfaceSymbol::SearchBuilds.to_string()))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.main_list);
+    }
+
+    fn draw_picker<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let filter = self.picker.as_ref().unwrap().filter.clone();
+        let items: Vec<ListItem> = self
+            .filtered_skills(&filter)
+            .into_iter()
+            .map(|skill| ListItem::new(skill.to_string()))
+            .collect();
+        let list = List::new(items)
+            .block(titled_block(format!(
+                "{}: {}",
+                InterfaceSymbol::SkillFilter,
+                filter
+            )))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.picker.as_mut().unwrap().list);
+    }
+
+    fn draw_results<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let title = match &self.running_search {
+            Some(search) => format!(
+                "{} ({}/{})",
+                InterfaceSymbol::TemplateBuildsFoundSoFar
+                    .to_string()
+                    .replace("{count}", &self.state.results.builds.len().to_string()),
+                search.done_steps,
+                search.job.step_count()
+            ),
+            None if self.state.results.builds.is_empty() => InterfaceSymbol::NoResult.to_string(),
+            None => self.state.results.builds.len().to_string(),
+        };
+        let items: Vec<ListItem> = self
+            .state
+            .results
+            .builds
+            .iter()
+            .map(|build| ListItem::new(build_summary(build)))
+            .collect();
+        let list = List::new(items)
+            .block(titled_block(title))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.results_list);
+    }
+
+    fn draw_details<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let build = match &self.focused_build {
+            Some(build) => build,
+            None => return,
+        };
+        let mut lines: Vec<Spans> = Vec::new();
+        for (slot, part) in ArmorSlot::ALL.iter().zip(build_parts(build)) {
+            let text = match part {
+                Some((armor, jewels)) => {
+                    let mut text = format!(
+                        "{:<10} {}",
+                        slot.symbol().to_string(),
+                        LocalizedArmor(armor)
+                    );
+                    for (size, skill) in place_jewels(&armor.slots, jewels) {
+                        text.push_str("  ");
+                        text.push_str(&slot_text(size, skill));
+                    }
+                    text
+                }
+                None => format!(
+                    "{:<10} {}",
+                    slot.symbol().to_string(),
+                    InterfaceSymbol::Free
+                ),
+            };
+            lines.push(Spans::from(text));
+        }
+        let weapon_jewels: Vec<String> = build
+            .weapon_jewels
+            .iter()
+            .flatten()
+            .map(|skill| LocalizedSkill(*skill).to_string())
+            .collect();
+        if !weapon_jewels.is_empty() {
+            lines.push(Spans::from(format!(
+                "{}: {}",
+                InterfaceSymbol::WeaponSlots,
+                weapon_jewels.join(", ")
+            )));
+        }
+
+        let resistances = total_resistances(build);
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(format!(
+            "{} {}  {} {}  {} {}  {} {}  {} {}  {} {}",
+            InterfaceSymbol::Defense,
+            total_defense(build),
+            InterfaceSymbol::Fire,
+            resistances[0],
+            InterfaceSymbol::Water,
+            resistances[1],
+            InterfaceSymbol::Thunder,
+            resistances[2],
+            InterfaceSymbol::Ice,
+            resistances[3],
+            InterfaceSymbol::Dragon,
+            resistances[4],
+        )));
+        lines.push(Spans::from(""));
+        for (skill, amount) in skill_totals(build).into_iter().rev() {
+            lines.push(Spans::from(format!("{} {}", LocalizedSkill(skill), amount)));
+        }
+
+        let title = match &self.build_name {
+            Some(name) => format!("{}: {}_", InterfaceSymbol::NewBuildName, name),
+            None => self.saved_build_name.clone(),
+        };
+        frame.render_widget(Paragraph::new(lines).block(titled_block(title)), area);
+    }
+
+    fn draw_builds<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let items: Vec<ListItem> = self
+            .sorted_build_names()
+            .into_iter()
+            .map(ListItem::new)
+            .collect();
+        let list = List::new(items)
+            .block(titled_block(InterfaceSymbol::ManageBuilds.to_string()))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.builds_list);
+    }
+
+    fn draw_talismans<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let items: Vec<ListItem> = self
+            .state
+            .talismans
+            .iter()
+            .map(|talisman| {
+                ListItem::new(talisman_summary(&TalismanDraft::from_talisman(talisman)))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(titled_block(InterfaceSymbol::ManageTalismans.to_string()))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.talismans_list);
+    }
+
+    fn draw_editor<B: Backend>(&mut self, frame: &mut Frame<B>, area: Rect) {
+        let mut items = vec![ListItem::new(format!(
+            "{}: {}",
+            InterfaceSymbol::TalismanName,
+            self.draft.name
+        ))];
+        for (skill, level) in self.draft.skills.iter() {
+            items.push(ListItem::new(format!(
+                "{} {}",
+                LocalizedSkill(*skill),
+                level
+            )));
+        }
+        for (index, size) in self.draft.slots.iter().enumerate() {
+            items.push(ListItem::new(format!(
+                "{} {}: {}",
+                InterfaceSymbol::Slots,
+                index + 1,
+                size
+            )));
+        }
+        let list = List::new(items)
+            .block(titled_block(InterfaceSymbol::Edit.to_string()))
+            .highlight_style(highlight());
+        frame.render_stateful_widget(list, area, &mut self.draft_list);
+    }
+}
+
+fn titled_block(title: String) -> Block<'static> {
+    Block::default().borders(Borders::ALL).title(title)
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
+
+// the defense then the name of each piece
+fn build_summary(build: &Build) -> String {
+    let pieces: Vec<String> = build_parts(build)
+        .into_iter()
+        .map(|part| match part {
+            Some((armor, _)) => LocalizedArmor(armor).to_string(),
+            None => InterfaceSymbol::Free.to_string(),
+        })
+        .collect();
+    format!("{:>4}  {}", total_defense(build), pieces.join(" | "))
+}
+
+fn talisman_summary(talisman: &TalismanDraft) -> String {
+    let skills: Vec<String> = talisman
+        .skills
+        .iter()
+        .map(|(skill, level)| format!("{} {}", LocalizedSkill(*skill), level))
+        .collect();
+    format!(
+        "{}  {}  [{}-{}-{}]",
+        talisman.name,
+        skills.join(", "),
+        talisman.slots[0],
+        talisman.slots[1],
+        talisman.slots[2]
+    )
+}
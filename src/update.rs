@@ -1,14 +1,12 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
-use crate::{ARMORS_PATH, LOCALE_DIR_PATH};
+use crate::paths::ARMOR_FILES;
 
 const ARMORS_BASE_URL: &str =
     "https://raw.githubusercontent.com/itytophile/monster-hunter-rise-armors/main/";
-const HELMETS_FILE: &str = "helmets.ron";
-const CHESTS_FILE: &str = "chests.ron";
-const ARMS_FILE: &str = "arms.ron";
-const WAISTS_FILE: &str = "waists.ron";
-const LEGS_FILE: &str = "legs.ron";
 
 async fn download_file(url: &str) -> Result<String, reqwest::Error> {
     let resp = reqwest::get(url).await?;
@@ -35,9 +33,9 @@ async fn download_and_write_file(base_url: &str, file: &str, dir: impl AsRef<Pat
     }
 }
 
-async fn download_armors() -> bool {
-    let futures = [HELMETS_FILE, CHESTS_FILE, ARMS_FILE, WAISTS_FILE, LEGS_FILE]
-        .map(|file| download_and_write_file(ARMORS_BASE_URL, file, ARMORS_PATH));
+async fn download_armors(armors_dir: &Path) -> bool {
+    let futures =
+        ARMOR_FILES.map(|file| download_and_write_file(ARMORS_BASE_URL, file, armors_dir));
 
     let bools = iced_futures::futures::future::join_all(futures).await;
     // all true = no problem
@@ -54,7 +52,7 @@ const POLISH_LOCALE: &str = "polish.ron";
 const RUSSIAN_LOCALE: &str = "russian.ron";
 const SPANISH_LOCALE: &str = "spanish.ron";
 
-async fn download_locales() -> bool {
+async fn download_locales(locale_dir: &Path) -> bool {
     let futures = [
         ENGLISH_LOCALE,
         FRENCH_LOCALE,
@@ -64,13 +62,18 @@ async fn download_locales() -> bool {
         RUSSIAN_LOCALE,
         SPANISH_LOCALE,
     ]
-    .map(|file| download_and_write_file(LOCALE_BASE_URL, file, LOCALE_DIR_PATH));
+    .map(|file| download_and_write_file(LOCALE_BASE_URL, file, locale_dir));
     let bools = iced_futures::futures::future::join_all(futures).await;
     // all true = no problem
     bools.iter().all(|b| *b)
 }
 
-pub async fn download_armors_and_locales() -> bool {
-    let bools = iced_futures::futures::future::join(download_armors(), download_locales()).await;
+// the directories are owned because the future outlives the application update
+pub async fn download_armors_and_locales(armors_dir: PathBuf, locale_dir: PathBuf) -> bool {
+    let bools = iced_futures::futures::future::join(
+        download_armors(&armors_dir),
+        download_locales(&locale_dir),
+    )
+    .await;
     bools.0 && bools.1
 }